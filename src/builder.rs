@@ -0,0 +1,138 @@
+use crate::{AdvInput, ColorChoice, PromptHistory, Theme};
+use rustyline::config::{Behavior, BellStyle, CompletionType, Config, EditMode};
+use rustyline::{Editor, Result};
+use std::path::PathBuf;
+
+/// Configures the editor of an `AdvInput`, see `AdvInput::builder()`.
+/// Unlike `AdvInput::new()`, `build()` returns an error instead of panicking
+pub struct AdvInputBuilder {
+    config: rustyline::config::Builder,
+    history: PromptHistory,
+    history_file: Option<PathBuf>,
+    history_size: Option<usize>,
+    history_dedup: Option<bool>,
+    auto_add_history: bool,
+    color: ColorChoice,
+    theme: Theme,
+}
+
+impl Default for AdvInputBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AdvInputBuilder {
+    /// the settings of `AdvInput::new()`
+    pub fn new() -> Self {
+        AdvInputBuilder {
+            config: Config::builder(),
+            history: PromptHistory::new(),
+            history_file: None,
+            history_size: None,
+            history_dedup: None,
+            auto_add_history: true,
+            color: ColorChoice::Auto,
+            theme: Theme::default(),
+        }
+    }
+
+    /// emacs (default) or vi key bindings
+    pub fn edit_mode(mut self, mode: EditMode) -> Self {
+        self.config = self.config.edit_mode(mode);
+        self
+    }
+
+    /// whether Tab cycles through the candidates (`Circular`, default) or lists them (`List`)
+    pub fn completion_type(mut self, completion_type: CompletionType) -> Self {
+        self.config = self.config.completion_type(completion_type);
+        self
+    }
+
+    /// audible, visible or no bell (e.g. when Tab finds nothing)
+    pub fn bell_style(mut self, style: BellStyle) -> Self {
+        self.config = self.config.bell_style(style);
+        self
+    }
+
+    /// the width of a tab character
+    pub fn tab_stop(mut self, tab_stop: u8) -> Self {
+        self.config = self.config.tab_stop(tab_stop);
+        self
+    }
+
+    /// starts with this history instead of an empty one
+    pub fn history(mut self, history: PromptHistory) -> Self {
+        self.history = history;
+        self
+    }
+
+    /// loads the history from `file` and saves it there, see `PromptHistory::set_file`
+    pub fn history_file(mut self, file: PathBuf) -> Self {
+        self.history_file = Some(file);
+        self
+    }
+
+    /// keeps at most `size` answers per prompt
+    pub fn history_size(mut self, size: usize) -> Self {
+        self.history_size = Some(size);
+        self
+    }
+
+    /// whether repeated answers are kept once only (the default of a new `PromptHistory`)
+    pub fn history_dedup(mut self, dedup: bool) -> Self {
+        self.history_dedup = Some(dedup);
+        self
+    }
+
+    /// whether answers are added to the history automatically (default); without it, the
+    /// history only holds what is added through `AdvInput::history_mut()`
+    pub fn auto_add_history(mut self, auto_add: bool) -> Self {
+        self.auto_add_history = auto_add;
+        self
+    }
+
+    /// whether the output is colored, see `AdvInput::set_color`. By default, colors are used
+    /// for terminals unless `NO_COLOR` or `TERM=dumb` say otherwise
+    pub fn color(mut self, color: ColorChoice) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// the styles of all output, see `Theme`
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// `Behavior::Stdio` (default) reads from stdin and writes to stdout;
+    /// `Behavior::PreferTerm` uses the terminal even if they are redirected
+    pub fn output_stream(mut self, behavior: Behavior) -> Self {
+        self.config = self.config.behavior(behavior);
+        self
+    }
+
+    /// creates the `AdvInput`; fails if the editor cannot be created or the history file cannot
+    /// be read
+    pub fn build(self) -> Result<AdvInput> {
+        let mut config = self.config;
+        let mut history = self.history;
+        if let Some(dedup) = self.history_dedup {
+            history.set_dedup(dedup);
+        }
+        if let Some(size) = self.history_size {
+            history.set_max_entries(size);
+            config = config.max_history_size(size)?;
+        }
+        if let Some(file) = self.history_file {
+            history.set_file(file)?;
+        }
+        let editor = Editor::with_config(config.build())?;
+        let mut adv = AdvInput::with_editor(editor);
+        adv.set_history(history);
+        adv.set_auto_add_history(self.auto_add_history);
+        adv.set_theme(self.theme);
+        adv.set_color(self.color);
+        Ok(adv)
+    }
+}
@@ -0,0 +1,102 @@
+use std::env;
+use std::ffi::OsString;
+use std::io::{self, IsTerminal};
+
+/// Whether the output of an `AdvInput` is colored, see `AdvInput::set_color`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorChoice {
+    /// colors for terminals only, following the environment (see `ColorChoice::enabled_for`)
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// The stream some output goes to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+impl ColorChoice {
+    /// whether output to `stream` is colored. With `Auto`, `CLICOLOR_FORCE` (not `0`) turns
+    /// colors on; otherwise `NO_COLOR` (not empty), `CLICOLOR=0` or `TERM=dumb` turn them off,
+    /// and they are only used if `stream` is a terminal
+    pub fn enabled_for(self, stream: Stream) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                let is_terminal = match stream {
+                    Stream::Stdout => io::stdout().is_terminal(),
+                    Stream::Stderr => io::stderr().is_terminal(),
+                };
+                auto_color(is_terminal, |name| env::var_os(name))
+            }
+        }
+    }
+}
+
+/* the decision of `Auto`, with `var` looking up environment variables */
+fn auto_color(is_terminal: bool, var: impl Fn(&str) -> Option<OsString>) -> bool {
+    if var("CLICOLOR_FORCE").is_some_and(|v| v != "0") {
+        return true;
+    }
+    let no_color = var("NO_COLOR").is_some_and(|v| !v.is_empty());
+    let clicolor_off = var("CLICOLOR").is_some_and(|v| v == "0");
+    let dumb = var("TERM").is_some_and(|t| t == "dumb");
+    !no_color && !clicolor_off && !dumb && is_terminal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /* `auto_color` with the given variables set */
+    fn auto(is_terminal: bool, vars: &[(&str, &str)]) -> bool {
+        auto_color(is_terminal, |name| {
+            vars.iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, v)| OsString::from(v))
+        })
+    }
+
+    #[test]
+    fn terminals_get_colors_by_default() {
+        assert!(auto(true, &[]));
+        assert!(!auto(false, &[]));
+        assert!(auto(true, &[("TERM", "xterm-256color"), ("CLICOLOR", "1")]));
+    }
+
+    #[test]
+    fn each_variable_turns_colors_off() {
+        assert!(!auto(true, &[("NO_COLOR", "1")]));
+        assert!(!auto(true, &[("CLICOLOR", "0")]));
+        assert!(!auto(true, &[("TERM", "dumb")]));
+        // an empty NO_COLOR counts as unset
+        assert!(auto(true, &[("NO_COLOR", "")]));
+    }
+
+    #[test]
+    fn clicolor_force_wins_over_everything() {
+        let all_off = [("NO_COLOR", "1"), ("CLICOLOR", "0"), ("TERM", "dumb")];
+        assert!(auto(false, &[("CLICOLOR_FORCE", "1")]));
+        assert!(auto(
+            false,
+            &[&all_off[..], &[("CLICOLOR_FORCE", "1")]].concat()
+        ));
+        assert!(!auto(
+            true,
+            &[&all_off[..], &[("CLICOLOR_FORCE", "0")]].concat()
+        ));
+        assert!(!auto(true, &[("CLICOLOR_FORCE", "0"), ("TERM", "dumb")]));
+        assert!(auto(true, &[("CLICOLOR_FORCE", "0")]));
+    }
+
+    #[test]
+    fn explicit_choices_ignore_the_environment() {
+        assert!(ColorChoice::Always.enabled_for(Stream::Stderr));
+        assert!(!ColorChoice::Never.enabled_for(Stream::Stdout));
+    }
+}
@@ -0,0 +1,485 @@
+use crate::{AdvInput, Navigation};
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+use std::fmt;
+use std::str::FromStr;
+
+/// Errors of the `InteractiveDeserializer`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeError {
+    /// the user pressed Ctrl-C / Ctrl-D
+    Cancelled,
+    /// raised by the deserialized type (e.g. a failed validation)
+    Message(String),
+}
+
+impl fmt::Display for DeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeError::Cancelled => write!(f, "input cancelled"),
+            DeError::Message(m) => write!(f, "{}", m),
+        }
+    }
+}
+
+impl std::error::Error for DeError {}
+
+impl de::Error for DeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        DeError::Message(msg.to_string())
+    }
+}
+
+/// A `serde::Deserializer` that asks the user for every value: struct fields, sequence
+/// elements (after asking for their number), map entries and enum variants (with tab
+/// completion of serde's variant names). This fills any `Deserialize` type, including
+/// third-party config structs:
+///
+/// ```ignore
+/// let config: ServerConfig = advanced_inputs::deserializer::from_prompts(&mut advi, "server")?;
+/// ```
+pub struct InteractiveDeserializer<'a> {
+    adv: &'a mut AdvInput,
+    path: Vec<String>,
+}
+
+/// asks the user for a complete `T`; `name` is the root of the field paths shown in the prompts
+pub fn from_prompts<T: DeserializeOwned>(adv: &mut AdvInput, name: &str) -> Result<T, DeError> {
+    let mut de = InteractiveDeserializer::new(adv, name);
+    T::deserialize(&mut de)
+}
+
+impl<'a> InteractiveDeserializer<'a> {
+    pub fn new(adv: &'a mut AdvInput, name: &str) -> Self {
+        InteractiveDeserializer {
+            adv,
+            path: vec![name.to_string()],
+        }
+    }
+
+    fn path(&self) -> String {
+        self.path.join(".")
+    }
+
+    /* runs `f` with `segment` appended to the path */
+    fn nested<T>(&mut self, segment: String, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.path.push(segment);
+        let result = f(self);
+        self.path.pop();
+        result
+    }
+
+    /* asks until the answer is accepted by `parse`; Ctrl-C / Ctrl-D cancel */
+    fn ask<T>(
+        &mut self,
+        type_hint: &str,
+        mut parse: impl FnMut(&str) -> Option<T>,
+    ) -> Result<T, DeError> {
+        let prompt = format!(
+            "{} ({}): ",
+            self.path(),
+            self.adv.out_theme().hint.paint(type_hint)
+        );
+        let was_enabled = self.adv.navigation_enabled();
+        self.adv.set_navigation(true);
+        let result = loop {
+            match self.adv.get_string(prompt.as_str()) {
+                Some(line) => match parse(&line) {
+                    Some(value) => break Ok(value),
+                    None => eprintln!(
+                        "{}",
+                        self.adv
+                            .err_theme()
+                            .warning
+                            .paint(&format!("Please enter a valid {}.", type_hint))
+                    ),
+                },
+                None => {
+                    if self.adv.take_navigation() == Some(Navigation::Cancel) {
+                        break Err(DeError::Cancelled);
+                    }
+                }
+            }
+        };
+        self.adv.set_navigation(was_enabled);
+        result
+    }
+
+    fn ask_parsed<T: FromStr>(&mut self, type_hint: &str) -> Result<T, DeError> {
+        self.ask(type_hint, |s| s.parse().ok())
+    }
+
+    fn ask_bool(&mut self, type_hint: &str) -> Result<bool, DeError> {
+        self.ask(type_hint, |s| match s.to_lowercase().as_str() {
+            "y" | "yes" | "true" => Some(true),
+            "n" | "no" | "false" => Some(false),
+            _ => None,
+        })
+    }
+
+    fn ask_variant(&mut self, variants: &'static [&'static str]) -> Result<&'static str, DeError> {
+        let choices: Vec<String> = variants.iter().map(|v| v.to_string()).collect();
+        let prompt = format!("{}: ", self.path());
+        let was_enabled = self.adv.navigation_enabled();
+        self.adv.set_navigation(true);
+        let result = loop {
+            match self
+                .adv
+                .get_choice_input(prompt.as_str(), &choices, None, true)
+            {
+                Some(choice) => {
+                    break Ok(variants
+                        .iter()
+                        .find(|v| **v == choice)
+                        .copied()
+                        .unwrap_or(variants[0]));
+                }
+                None => {
+                    if self.adv.take_navigation() == Some(Navigation::Cancel) {
+                        break Err(DeError::Cancelled);
+                    }
+                    eprintln!(
+                        "{}",
+                        self.adv
+                            .err_theme()
+                            .warning
+                            .paint("Please choose one of the listed variants.")
+                    );
+                }
+            }
+        };
+        self.adv.set_navigation(was_enabled);
+        result
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &mut InteractiveDeserializer<'_> {
+    type Error = DeError;
+
+    /* the type is unknown (e.g. serde_json::Value): infer it from the text */
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        let line = self.ask("value", |s| Some(s.to_string()))?;
+        if let Ok(b) = line.parse::<bool>() {
+            visitor.visit_bool(b)
+        } else if let Ok(i) = line.parse::<i64>() {
+            visitor.visit_i64(i)
+        } else if let Ok(f) = line.parse::<f64>() {
+            visitor.visit_f64(f)
+        } else {
+            visitor.visit_string(line)
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_bool(self.ask_bool("yes/no")?)
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_i8(self.ask_parsed("i8")?)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_i16(self.ask_parsed("i16")?)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_i32(self.ask_parsed("i32")?)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_i64(self.ask_parsed("i64")?)
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_u8(self.ask_parsed("u8")?)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_u16(self.ask_parsed("u16")?)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_u32(self.ask_parsed("u32")?)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_u64(self.ask_parsed("u64")?)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_f32(self.ask_parsed("f32")?)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_f64(self.ask_parsed("f64")?)
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_char(self.ask_parsed("single character")?)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_string(self.ask("text", |s| Some(s.to_string()))?)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        self.deserialize_byte_buf(visitor)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_byte_buf(self.ask("text", |s| Some(s.as_bytes().to_vec()))?)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        if self.ask_bool("set it? yes/no")? {
+            visitor.visit_some(self)
+        } else {
+            visitor.visit_none()
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        let len = self.nested("len".to_string(), |de| {
+            de.ask_parsed::<usize>("number of elements")
+        })?;
+        visitor.visit_seq(Elements {
+            de: self,
+            index: 0,
+            len,
+        })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        visitor.visit_seq(Elements {
+            de: self,
+            index: 0,
+            len,
+        })
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        let len = self.nested("len".to_string(), |de| {
+            de.ask_parsed::<usize>("number of entries")
+        })?;
+        visitor.visit_map(Entries {
+            de: self,
+            index: 0,
+            len,
+        })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        println!(
+            "{}",
+            self.adv
+                .out_theme()
+                .heading
+                .paint(&format!("{}:", self.path()))
+        );
+        visitor.visit_map(Fields {
+            de: self,
+            fields,
+            index: 0,
+        })
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        let variant = self.ask_variant(variants)?;
+        visitor.visit_enum(Variant { de: self, variant })
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        self.deserialize_string(visitor)
+    }
+
+    /* nothing to ask for a value that is thrown away */
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_unit()
+    }
+}
+
+/* sequence and tuple elements, prompted as `path[i]` */
+struct Elements<'a, 'b> {
+    de: &'a mut InteractiveDeserializer<'b>,
+    index: usize,
+    len: usize,
+}
+
+impl<'de> SeqAccess<'de> for Elements<'_, '_> {
+    type Error = DeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, DeError> {
+        if self.index >= self.len {
+            return Ok(None);
+        }
+        let segment = format!("[{}]", self.index);
+        self.index += 1;
+        self.de
+            .nested(segment, |de| seed.deserialize(&mut *de))
+            .map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len - self.index)
+    }
+}
+
+/* map entries, prompted as `path.key#i` and `path.value#i` */
+struct Entries<'a, 'b> {
+    de: &'a mut InteractiveDeserializer<'b>,
+    index: usize,
+    len: usize,
+}
+
+impl<'de> MapAccess<'de> for Entries<'_, '_> {
+    type Error = DeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, DeError> {
+        if self.index >= self.len {
+            return Ok(None);
+        }
+        let segment = format!("key#{}", self.index + 1);
+        self.de
+            .nested(segment, |de| seed.deserialize(&mut *de))
+            .map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, DeError> {
+        let segment = format!("value#{}", self.index + 1);
+        self.index += 1;
+        self.de.nested(segment, |de| seed.deserialize(&mut *de))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len - self.index)
+    }
+}
+
+/* struct fields: the keys come from serde's field list, only the values are prompted */
+struct Fields<'a, 'b> {
+    de: &'a mut InteractiveDeserializer<'b>,
+    fields: &'static [&'static str],
+    index: usize,
+}
+
+impl<'de> MapAccess<'de> for Fields<'_, '_> {
+    type Error = DeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, DeError> {
+        match self.fields.get(self.index) {
+            Some(field) => seed.deserialize(field.into_deserializer()).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, DeError> {
+        let segment = self.fields[self.index].to_string();
+        self.index += 1;
+        self.de.nested(segment, |de| seed.deserialize(&mut *de))
+    }
+}
+
+/* the chosen enum variant and its content */
+struct Variant<'a, 'b> {
+    de: &'a mut InteractiveDeserializer<'b>,
+    variant: &'static str,
+}
+
+impl<'de, 'a, 'b> EnumAccess<'de> for Variant<'a, 'b> {
+    type Error = DeError;
+    type Variant = Self;
+
+    fn variant_seed<S: DeserializeSeed<'de>>(self, seed: S) -> Result<(S::Value, Self), DeError> {
+        let value = seed.deserialize(self.variant.into_deserializer())?;
+        Ok((value, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for Variant<'_, '_> {
+    type Error = DeError;
+
+    fn unit_variant(self) -> Result<(), DeError> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, DeError> {
+        let segment = self.variant.to_string();
+        self.de.nested(segment, |de| seed.deserialize(&mut *de))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, DeError> {
+        let segment = self.variant.to_string();
+        self.de.nested(segment, |de| {
+            de::Deserializer::deserialize_tuple(&mut *de, len, visitor)
+        })
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        let segment = self.variant.to_string();
+        self.de.nested(segment, |de| {
+            de::Deserializer::deserialize_struct(&mut *de, "", fields, visitor)
+        })
+    }
+}
@@ -0,0 +1,170 @@
+use crate::PromptableEnum;
+use crate::store::{data_file, load_records, save_records};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::io;
+use std::path::PathBuf;
+
+/// How often the variants of each enum type were picked. While enabled, enum prompts offer the
+/// variants users pick most first (in the printed list and in Tab completion), after the pinned
+/// variants. With a file set, the counts are loaded from it and saved after every pick
+#[derive(Debug, Clone, Default)]
+pub struct EnumUsage {
+    enabled: bool,
+    counts: BTreeMap<String, BTreeMap<String, usize>>,
+    pinned: BTreeMap<String, Vec<String>>,
+    file: Option<PathBuf>,
+}
+
+impl EnumUsage {
+    /// no counts, disabled, kept in memory only
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// enables (or disables) counting and ranking
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// keeps the counts in `file` (adding up the ones saved there), like
+    /// `PromptHistory::set_file` does
+    pub fn set_file(&mut self, file: PathBuf) -> io::Result<()> {
+        for record in load_records(&file)? {
+            if let [key, variant, count] = &record[..]
+                && let Ok(count) = count.parse::<usize>()
+            {
+                *self
+                    .counts
+                    .entry(key.clone())
+                    .or_default()
+                    .entry(variant.clone())
+                    .or_default() += count;
+            }
+        }
+        self.file = Some(file);
+        Ok(())
+    }
+
+    /// `<name>.usage` next to `PromptHistory::default_file`
+    pub fn default_file(name: &str) -> Option<PathBuf> {
+        data_file(name, "usage")
+    }
+
+    /// always offers these variants first, in this order, regardless of their counts
+    pub fn pin<E: PromptableEnum>(&mut self, variants: &[E]) {
+        let names = variants.iter().map(|v| v.display_name()).collect();
+        self.pinned.insert(E::usage_key(), names);
+    }
+
+    /// how often `variant` was picked
+    pub fn count<E: PromptableEnum>(&self, variant: &E) -> usize {
+        self.count_of(&E::usage_key(), &variant.display_name())
+    }
+
+    /// counts a pick of `variant` (if enabled) and saves the counts if they have a file
+    pub fn record<E: PromptableEnum>(&mut self, variant: &E) -> io::Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+        *self
+            .counts
+            .entry(E::usage_key())
+            .or_default()
+            .entry(variant.display_name())
+            .or_default() += 1;
+        self.save()
+    }
+
+    /// the variant names of `E`: the pinned ones first, then the others by how often they were
+    /// picked (ties in declaration order). Declaration order while disabled
+    pub fn ranked<E: PromptableEnum>(&self) -> Vec<String> {
+        let mut variants = E::variants_as_strings();
+        if !self.enabled {
+            return variants;
+        }
+        let key = E::usage_key();
+        let pinned = self.pinned.get(&key).map_or(&[][..], |p| p.as_slice());
+        // pinned variants get the highest ranks, in their given order
+        variants.sort_by_key(|v| match pinned.iter().position(|p| p == v) {
+            Some(i) => (0, i, Reverse(0)),
+            None => (1, 0, Reverse(self.count_of(&key, v))),
+        });
+        variants
+    }
+
+    /// writes the counts to their file (if they have one)
+    pub fn save(&self) -> io::Result<()> {
+        let records = self.counts.iter().flat_map(|(key, counts)| {
+            counts
+                .iter()
+                .map(move |(variant, count)| vec![key.clone(), variant.clone(), count.to_string()])
+        });
+        save_records(self.file.as_deref(), records)
+    }
+
+    fn count_of(&self, key: &str, variant: &str) -> usize {
+        self.counts
+            .get(key)
+            .and_then(|c| c.get(variant))
+            .copied()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::promptable_enum::{Display, EnumIter};
+
+    #[derive(Debug, Clone, PartialEq, EnumIter, Display)]
+    enum Weapon {
+        Sword,
+        Bow,
+        LongSpear,
+        Axe,
+    }
+
+    impl PromptableEnum for Weapon {}
+
+    fn usage_with(picks: &[Weapon]) -> EnumUsage {
+        let mut usage = EnumUsage::new();
+        usage.set_enabled(true);
+        for pick in picks {
+            usage.record(pick).unwrap();
+        }
+        usage
+    }
+
+    #[test]
+    fn variants_rank_pinned_first_then_by_count() {
+        let mut usage = usage_with(&[Weapon::Axe, Weapon::Bow, Weapon::Axe, Weapon::LongSpear]);
+        assert_eq!(usage.count(&Weapon::Axe), 2);
+        // Bow and Long Spear tie, so they keep their declaration order
+        assert_eq!(
+            usage.ranked::<Weapon>(),
+            ["Axe", "Bow", "Long Spear", "Sword"]
+        );
+        usage.pin(&[Weapon::Sword, Weapon::LongSpear]);
+        assert_eq!(
+            usage.ranked::<Weapon>(),
+            ["Sword", "Long Spear", "Axe", "Bow"]
+        );
+    }
+
+    #[test]
+    fn disabled_usage_keeps_declaration_order_and_counts_nothing() {
+        let mut usage = usage_with(&[Weapon::Axe]);
+        usage.set_enabled(false);
+        usage.record(&Weapon::Axe).unwrap();
+        assert_eq!(usage.count(&Weapon::Axe), 1);
+        assert_eq!(
+            usage.ranked::<Weapon>(),
+            ["Sword", "Bow", "Long Spear", "Axe"]
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use crate::RecentFiles;
 use rustyline::{
     completion::{Completer, Pair},
     highlight::Highlighter,
@@ -5,17 +6,619 @@ use rustyline::{
     validate::Validator,
 };
 use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, read_dir};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
 
+/// Decides which files are offered (and accepted) by `AdvInput::get_file_input`
+#[derive(Clone)]
+pub enum FileFilter {
+    /// every file
+    All,
+    /// no files at all, only directories (see `AdvInput::get_dir_input`)
+    NoFiles,
+    /// files with one of these extensions (without the dot); a typed name without extension
+    /// gets the first one appended
+    Extensions(Vec<String>),
+    /// files whose name matches a glob pattern like `save_*.json`
+    Glob(glob::Pattern),
+    /// files accepted by the predicate
+    Predicate(Arc<dyn Fn(&Path) -> bool + Send + Sync>),
+}
+
+impl FileFilter {
+    pub fn extensions(extensions: &[&str]) -> Self {
+        FileFilter::Extensions(extensions.iter().map(|e| e.to_string()).collect())
+    }
+
+    /// returns `None` if `pattern` is not a valid glob pattern
+    pub fn glob(pattern: &str) -> Option<Self> {
+        glob::Pattern::new(pattern).ok().map(FileFilter::Glob)
+    }
+
+    pub fn predicate(predicate: impl Fn(&Path) -> bool + Send + Sync + 'static) -> Self {
+        FileFilter::Predicate(Arc::new(predicate))
+    }
+
+    /// returns true if the file at `path` passes the filter
+    pub fn matches(&self, path: &Path) -> bool {
+        match self {
+            FileFilter::All => true,
+            FileFilter::NoFiles => false,
+            FileFilter::Extensions(exts) => path
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| exts.iter().any(|x| x.eq_ignore_ascii_case(e))),
+            FileFilter::Glob(pattern) => path
+                .file_name()
+                .and_then(|f| f.to_str())
+                .is_some_and(|f| pattern.matches(f)),
+            FileFilter::Predicate(predicate) => predicate(path),
+        }
+    }
+
+    /* appends the default extension to names without one; names to save get it whenever they
+    do not end in one of the extensions (`save.v2` becomes `save.v2.json`) */
+    pub(crate) fn complete_name(&self, mut path: PathBuf, save: bool) -> PathBuf {
+        if let FileFilter::Extensions(exts) = self
+            && let Some(ext) = exts.first()
+        {
+            if path.extension().is_none() {
+                path.set_extension(ext);
+            } else if save && !self.matches(&path) {
+                path.as_mut_os_string().push(format!(".{}", ext));
+            }
+        }
+        path
+    }
+}
+
+/// Checks the content of existing files: files it rejects are marked in the listing and not
+/// accepted by `AdvInput::get_file_input`
+#[derive(Clone)]
+pub struct FileValidator(CheckFn);
+
+type CheckFn = Arc<dyn Fn(&Path) -> Result<(), String> + Send + Sync>;
+
+impl FileValidator {
+    /// `check` returns the reason why a file is not acceptable
+    pub fn new(check: impl Fn(&Path) -> Result<(), String> + Send + Sync + 'static) -> Self {
+        FileValidator(Arc::new(check))
+    }
+
+    /// accepts files that parse as JSON
+    #[cfg(feature = "serde")]
+    pub fn json() -> Self {
+        Self::json_shape(|_| Ok(()))
+    }
+
+    /// accepts files that parse as JSON and pass `shape`, e.g. a check for required keys
+    #[cfg(feature = "serde")]
+    pub fn json_shape(
+        shape: impl Fn(&serde_json::Value) -> Result<(), String> + Send + Sync + 'static,
+    ) -> Self {
+        FileValidator::new(move |path| {
+            let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+            let value = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+            shape(&value)
+        })
+    }
+
+    pub fn check(&self, path: &Path) -> Result<(), String> {
+        (self.0)(path)
+    }
+}
+
+impl fmt::Debug for FileValidator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("FileValidator")
+    }
+}
+
+/// Whether the chosen file has to exist
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FileMode {
+    MustExist,
+    MustNotExist,
+    #[default]
+    Either,
+}
+
+/// Options for `AdvInput::get_file_input`
+#[derive(Debug, Clone, Default)]
+pub struct FileInputOptions {
+    /// offer (and accept) directories, too
+    pub include_dirs: bool,
+    /// offer names starting with a dot
+    pub show_hidden: bool,
+    pub mode: FileMode,
+    /// file name (relative to the directory) used for an empty input
+    pub default_name: Option<String>,
+    /// list (and complete) files newest first, with their size and modification time
+    pub details: bool,
+    /// with `details`, the value of this top-level key of JSON files is shown as preview, e.g.
+    /// `"name"` (needs the `serde` feature)
+    pub preview_key: Option<String>,
+    /// checks the content of existing files, see `FileValidator`
+    pub validator: Option<FileValidator>,
+    /// recently chosen files, offered first (see `RecentFiles`)
+    pub recent: Option<RecentFiles>,
+    /// only accept (and complete) paths inside the directory, after resolving `..` and
+    /// symlinks
+    pub confine: bool,
+    /// how long the completion reuses a directory scan; `None` means `DEFAULT_CACHE_TTL`
+    pub cache_ttl: Option<Duration>,
+}
+
+impl FileInputOptions {
+    /* the reason why the existing file at `path` is rejected by the validator */
+    pub(crate) fn invalid_reason(&self, path: &Path) -> Option<String> {
+        match &self.validator {
+            Some(validator) if path.is_file() => validator.check(path).err(),
+            _ => None,
+        }
+    }
+}
+
+/// The result of a file prompt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileChoice {
+    /// the user chose an existing file
+    Existing(PathBuf),
+    /// the user entered the name of a file that does not exist yet
+    New(PathBuf),
+    /// the input was empty and the caller-supplied default is used (it may not exist)
+    Default(PathBuf),
+    /// Ctrl-C / Ctrl-D, or an empty input without default
+    Cancelled,
+}
+
+impl FileChoice {
+    /// returns the chosen path, unless the prompt was cancelled
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            FileChoice::Existing(p) | FileChoice::New(p) | FileChoice::Default(p) => Some(p),
+            FileChoice::Cancelled => None,
+        }
+    }
+
+    pub fn into_path(self) -> Option<PathBuf> {
+        match self {
+            FileChoice::Existing(p) | FileChoice::New(p) | FileChoice::Default(p) => Some(p),
+            FileChoice::Cancelled => None,
+        }
+    }
+}
+
+/// What happens to an existing file before it is overwritten
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BackupPolicy {
+    #[default]
+    None,
+    /// copy it to `name.json.bak`
+    Single,
+    /// keep up to n copies: `name.json.bak.1` (newest) to `name.json.bak.n`
+    Numbered(usize),
+}
+
+/// Options for `AdvInput::get_save_file_input`
+#[derive(Debug, Clone, Default)]
+pub struct SaveOptions {
+    pub backup: BackupPolicy,
+    /// file name (relative to the directory) used for an empty input
+    pub default_name: Option<String>,
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// copies `path` to its backup according to `policy`, rotating numbered backups.
+/// Returns the path of the new backup, if one was made
+pub fn backup_file(path: &Path, policy: BackupPolicy) -> io::Result<Option<PathBuf>> {
+    let backup = match policy {
+        BackupPolicy::None => return Ok(None),
+        BackupPolicy::Numbered(0) => return Ok(None),
+        BackupPolicy::Single => with_suffix(path, ".bak"),
+        BackupPolicy::Numbered(keep) => {
+            let oldest = with_suffix(path, &format!(".bak.{}", keep));
+            if oldest.exists() {
+                fs::remove_file(&oldest)?;
+            }
+            for n in (1..keep).rev() {
+                let from = with_suffix(path, &format!(".bak.{}", n));
+                if from.exists() {
+                    fs::rename(&from, with_suffix(path, &format!(".bak.{}", n + 1)))?;
+                }
+            }
+            with_suffix(path, ".bak.1")
+        }
+    };
+    fs::copy(path, &backup)?;
+    Ok(Some(backup))
+}
+
+/// checks that `path` can be written: its directory must exist and accept new files, and an
+/// existing file must not be read-only
+pub fn check_writable(path: &Path) -> io::Result<()> {
+    let dir = match path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p,
+        _ => Path::new("."),
+    };
+    if !dir.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "the directory does not exist",
+        ));
+    }
+    if path.exists() && fs::metadata(path)?.permissions().readonly() {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "the file is read-only",
+        ));
+    }
+    check_dir_writable(dir)
+}
+
+/// checks that new files can be created in `dir`
+pub fn check_dir_writable(dir: &Path) -> io::Result<()> {
+    // the only reliable check is to actually create a file
+    let probe = dir.join(format!(".advinput-write-test-{}", std::process::id()));
+    fs::File::create(&probe)?;
+    fs::remove_file(&probe)
+}
+
+/// Options for `AdvInput::get_dir_input`
+#[derive(Debug, Clone, Default)]
+pub struct DirInputOptions {
+    /// offer to create a missing directory (after confirmation)
+    pub create_missing: bool,
+    /// only accept directories new files can be created in
+    pub require_writable: bool,
+    /// offer names starting with a dot
+    pub show_hidden: bool,
+    /// directory (relative to the base directory) used for an empty input, e.g. `"."`
+    pub default_name: Option<String>,
+}
+
+/// lists the names in `dir` that pass `filter` (directories get a trailing `/`), sorted by
+/// name, or newest first if `options.details` is set
+pub fn list_dir(dir: &Path, filter: &FileFilter, options: &FileInputOptions) -> Vec<String> {
+    let mut names = Vec::new();
+    let entries = match read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return names,
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Some(name) = path.file_name().and_then(|f| f.to_str()) else {
+            continue;
+        };
+        if name.starts_with('.') && !options.show_hidden {
+            continue;
+        }
+        if options.confine && !is_within(dir, &path) {
+            continue;
+        }
+        if path.is_dir() {
+            if options.include_dirs {
+                names.push(format!("{}/", name));
+            }
+        } else if filter.matches(&path) {
+            names.push(name.to_string());
+        }
+    }
+    names.sort();
+    if options.details {
+        names.sort_by_key(|name| std::cmp::Reverse(modified(&dir.join(name))));
+    }
+    names
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// describes the file at `path` for a detailed listing: size, age and (for JSON files with
+/// `preview_key` set) a preview, e.g. `"1.2 KiB, 3 h ago, Aragorn"`
+pub fn file_details(path: &Path, preview_key: Option<&str>) -> String {
+    let Ok(metadata) = fs::metadata(path) else {
+        return String::new();
+    };
+    let mut parts = Vec::new();
+    if metadata.is_file() {
+        parts.push(format_size(metadata.len()));
+    }
+    if let Ok(time) = metadata.modified() {
+        parts.push(format_age(time));
+    }
+    if let Some(key) = preview_key
+        && metadata.is_file()
+        && let Some(preview) = json_preview(path, key)
+    {
+        parts.push(preview);
+    }
+    parts.join(", ")
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+fn format_age(time: SystemTime) -> String {
+    let secs = SystemTime::now()
+        .duration_since(time)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    match secs {
+        0..60 => "just now".to_string(),
+        60..3600 => format!("{} min ago", secs / 60),
+        3600..86400 => format!("{} h ago", secs / 3600),
+        _ => match secs / 86400 {
+            1 => "yesterday".to_string(),
+            days => format!("{} days ago", days),
+        },
+    }
+}
+
+/* the value of a top-level key of a (not too large) JSON file, shortened to one line */
+#[cfg(feature = "serde")]
+fn json_preview(path: &Path, key: &str) -> Option<String> {
+    const MAX_FILE_SIZE: u64 = 1 << 20;
+    const MAX_LEN: usize = 40;
+    if !path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("json"))
+        || fs::metadata(path).ok()?.len() > MAX_FILE_SIZE
+    {
+        return None;
+    }
+    let value: serde_json::Value = serde_json::from_str(&fs::read_to_string(path).ok()?).ok()?;
+    let text = match value.get(key)? {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    let line = text.lines().next().unwrap_or("");
+    if line.chars().count() > MAX_LEN {
+        Some(format!(
+            "{}…",
+            line.chars().take(MAX_LEN - 1).collect::<String>()
+        ))
+    } else {
+        Some(line.to_string())
+    }
+}
+
+#[cfg(not(feature = "serde"))]
+fn json_preview(_path: &Path, _key: &str) -> Option<String> {
+    None
+}
+
+/// how long `FileCompleterHelper` reuses a directory scan by default
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(1);
+
+/// a rustyline helper completing paths: it lists the directory the typed prefix points to
+/// (relative to `base_dir`, absolute, with `~` or `..`), appends `/` to directories so the user
+/// can descend, escapes spaces and offers only the files passing the filter.
+/// Directories are re-scanned on Tab once their cached scan is older than the cache TTL, so
+/// files created while the prompt is open show up without rescanning on every key press
 pub struct FileCompleterHelper {
-    files: Vec<String>,
+    base_dir: PathBuf,
+    filter: FileFilter,
+    options: FileInputOptions,
+    cache: Mutex<ScanCache>,
+}
+
+/* the candidates of each scanned directory, with the time of the scan */
+type ScanCache = HashMap<PathBuf, (Instant, Arc<Vec<Candidate>>)>;
+
+/* a completion candidate of a scanned directory */
+struct Candidate {
+    /// the file name, directories with a trailing `/`
+    name: String,
+    /// the name plus details, see `FileInputOptions::details`
+    display: String,
+    modified: Option<SystemTime>,
 }
 
 impl FileCompleterHelper {
-    pub fn new(files: Vec<String>) -> Self {
-        FileCompleterHelper { files }
+    pub fn new(base_dir: PathBuf, filter: FileFilter, options: FileInputOptions) -> Self {
+        FileCompleterHelper {
+            base_dir,
+            filter,
+            options,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /* the completions of `word`, an unescaped path; the replacements are unescaped as well */
+    pub(crate) fn complete_word(&self, word: &str) -> Vec<Pair> {
+        let (dir_part, prefix) = match word.rfind('/') {
+            Some(i) => word.split_at(i + 1),
+            None => ("", word),
+        };
+        let dir = resolve_path(&self.base_dir, &escape_path(dir_part));
+        let show_hidden = self.options.show_hidden || prefix.starts_with('.');
+        self.candidates(&dir)
+            .iter()
+            .filter(|c| c.name.starts_with(prefix) && (show_hidden || !c.name.starts_with('.')))
+            .map(|c| Pair {
+                display: c.display.clone(),
+                replacement: format!("{}{}", dir_part, c.name),
+            })
+            .collect()
+    }
+
+    /* the candidates of `dir`, from the cache while it is fresh */
+    fn candidates(&self, dir: &Path) -> Arc<Vec<Candidate>> {
+        let ttl = self.options.cache_ttl.unwrap_or(DEFAULT_CACHE_TTL);
+        // a panic while scanning leaves nothing half-written, so a poisoned cache is fine
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((scanned, candidates)) = cache.get(dir)
+            && scanned.elapsed() < ttl
+        {
+            return candidates.clone();
+        }
+        let candidates = Arc::new(self.scan(dir));
+        cache.insert(dir.to_path_buf(), (Instant::now(), candidates.clone()));
+        candidates
+    }
+
+    fn scan(&self, dir: &Path) -> Vec<Candidate> {
+        let mut candidates = Vec::new();
+        let Ok(entries) = read_dir(dir) else {
+            return candidates;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(name) = path.file_name().and_then(|f| f.to_str()) else {
+                continue;
+            };
+            if self.options.confine && !is_within(&self.base_dir, &path) {
+                continue;
+            }
+            // directories are always offered, so the user can descend into them
+            let name = if path.is_dir() {
+                format!("{}/", name)
+            } else if self.filter.matches(&path) {
+                name.to_string()
+            } else {
+                continue;
+            };
+            let invalid = self.options.invalid_reason(&path).is_some();
+            let display = match (self.options.details, invalid) {
+                (true, _) => {
+                    let details = file_details(&path, self.options.preview_key.as_deref());
+                    let mark = if invalid { ", invalid" } else { "" };
+                    format!("{}  ({}{})", name, details, mark)
+                }
+                (false, true) => format!("{}  (invalid)", name),
+                (false, false) => name.clone(),
+            };
+            candidates.push(Candidate {
+                name,
+                display,
+                modified: modified(&path),
+            });
+        }
+        candidates.sort_by(|a, b| a.display.cmp(&b.display));
+        if self.options.details {
+            candidates.sort_by_key(|c| std::cmp::Reverse(c.modified));
+        }
+        candidates
     }
 }
 
+/* escapes characters the completion (and `expand_path`) treat specially */
+fn escape_path(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if is_escaped(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/* the characters `escape_path` puts a backslash before */
+fn is_escaped(c: char) -> bool {
+    c.is_whitespace() || c == '\\' || c == '"' || c == '\''
+}
+
+/* other backslashes are kept, e.g. the separators of `C:\saves\a.json` */
+fn unescape_path(s: &str) -> String {
+    let mut unescaped = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match chars.peek() {
+            Some(&next) if c == '\\' && is_escaped(next) => {
+                unescaped.push(next);
+                chars.next();
+            }
+            _ => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+}
+
+/// turns typed input into a path: removes the backslash escapes added by the completion and
+/// expands a leading `~` to the home directory
+pub fn expand_path(input: &str) -> PathBuf {
+    let unescaped = unescape_path(input.trim());
+    if unescaped == "~" {
+        return home_dir().unwrap_or_else(|| PathBuf::from("~"));
+    }
+    if let Some(rest) = unescaped.strip_prefix("~/")
+        && let Some(home) = home_dir()
+    {
+        return home.join(rest);
+    }
+    PathBuf::from(unescaped)
+}
+
+/// resolves typed input (see `expand_path`) relative to `dir`, unless it is absolute
+pub fn resolve_path(dir: &Path, input: &str) -> PathBuf {
+    let path = expand_path(input);
+    if path.is_absolute() {
+        path
+    } else {
+        dir.join(path)
+    }
+}
+
+/* the file typed as `input`: resolved relative to `dir` and completed with the extension of
+`filter`, unless it names a directory (an existing one, or with a trailing separator) */
+pub(crate) fn typed_path(dir: &Path, filter: &FileFilter, input: &str, save: bool) -> PathBuf {
+    let path = resolve_path(dir, input);
+    if path.is_dir() || input.ends_with(['/', std::path::MAIN_SEPARATOR]) {
+        path
+    } else {
+        filter.complete_name(path, save)
+    }
+}
+
+/// returns true if `path` lies inside `root` once both are canonicalized (resolving `..` and
+/// symlinks). For a path that does not exist yet, its nearest existing ancestor is checked
+pub fn is_within(root: &Path, path: &Path) -> bool {
+    let Ok(root) = root.canonicalize() else {
+        return false;
+    };
+    let mut existing = path;
+    while !existing.exists() {
+        // `file_name()` is `None` for a trailing `..`, which could lead anywhere
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(_)) => existing = parent,
+            _ => return false,
+        }
+    }
+    existing
+        .canonicalize()
+        .is_ok_and(|existing| existing.starts_with(&root))
+}
+
 impl Completer for FileCompleterHelper {
     type Candidate = Pair;
 
@@ -25,17 +628,24 @@ impl Completer for FileCompleterHelper {
         pos: usize,
         _ctx: &rustyline::Context<'_>,
     ) -> rustyline::Result<(usize, Vec<Self::Candidate>)> {
-        let word_start = line[..pos]
-            .rfind(|c: char| c.is_whitespace() || c == '/')
-            .map_or(0, |i| i + 1);
-        let word = &line[word_start..pos];
+        /* the path starts after the last whitespace that is not escaped */
+        let mut word_start = 0;
+        let mut escaped = false;
+        for (i, c) in line[..pos].char_indices() {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c.is_whitespace() {
+                word_start = i + c.len_utf8();
+            }
+        }
         let matches = self
-            .files
-            .iter()
-            .filter(|f| f.starts_with(word))
-            .map(|f| Pair {
-                display: f.clone(),
-                replacement: f.clone(),
+            .complete_word(&unescape_path(&line[word_start..pos]))
+            .into_iter()
+            .map(|pair| Pair {
+                replacement: escape_path(&pair.replacement),
+                ..pair
             })
             .collect();
         Ok((word_start, matches))
@@ -57,3 +667,176 @@ impl Hinter for FileCompleterHelper {
 
 impl Validator for FileCompleterHelper {}
 impl rustyline::Helper for FileCompleterHelper {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /* a fresh directory `root` with `root/sub/a.json`, and a directory `outside` next to it */
+    struct Sandbox {
+        base: PathBuf,
+        root: PathBuf,
+        outside: PathBuf,
+    }
+
+    impl Sandbox {
+        fn new(name: &str) -> Self {
+            let base = std::env::temp_dir().join(format!(
+                "advanced_inputs_{}_{}",
+                name,
+                std::process::id()
+            ));
+            let _ = fs::remove_dir_all(&base);
+            let root = base.join("root");
+            let outside = base.join("outside");
+            fs::create_dir_all(root.join("sub")).unwrap();
+            fs::create_dir_all(&outside).unwrap();
+            fs::write(root.join("sub").join("a.json"), "{}").unwrap();
+            fs::write(outside.join("secret.json"), "{}").unwrap();
+            Sandbox {
+                base,
+                root,
+                outside,
+            }
+        }
+
+        fn within(&self, input: &str) -> bool {
+            is_within(&self.root, &resolve_path(&self.root, input))
+        }
+    }
+
+    impl Drop for Sandbox {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.base);
+        }
+    }
+
+    #[test]
+    fn paths_inside_the_root_are_accepted() {
+        let sandbox = Sandbox::new("inside");
+        assert!(sandbox.within("sub/a.json"));
+        assert!(sandbox.within("sub/new.json"));
+        assert!(sandbox.within("./sub/../sub/a.json"));
+        assert!(sandbox.within("new/deeper/x.json"));
+        let absolute = sandbox.root.join("sub").join("a.json");
+        assert!(sandbox.within(absolute.to_str().unwrap()));
+    }
+
+    #[test]
+    fn parent_dirs_leading_out_are_rejected() {
+        let sandbox = Sandbox::new("dotdot");
+        assert!(!sandbox.within(".."));
+        assert!(!sandbox.within("../outside/secret.json"));
+        assert!(!sandbox.within("sub/../../outside/secret.json"));
+        assert!(!sandbox.within("sub/../../outside/new.json"));
+    }
+
+    #[test]
+    fn missing_dirs_followed_by_parent_dirs_are_rejected() {
+        let sandbox = Sandbox::new("missing");
+        // `new` does not exist, so `new/..` cannot be resolved (nor created)
+        assert!(!sandbox.within("new/../x.json"));
+        assert!(!sandbox.within("new/../../outside/x.json"));
+    }
+
+    #[test]
+    fn absolute_paths_outside_are_rejected() {
+        let sandbox = Sandbox::new("absolute");
+        let secret = sandbox.outside.join("secret.json");
+        assert!(!sandbox.within(secret.to_str().unwrap()));
+        assert!(!sandbox.within("/"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_pointing_outside_are_rejected() {
+        let sandbox = Sandbox::new("symlink");
+        std::os::unix::fs::symlink(&sandbox.outside, sandbox.root.join("escape")).unwrap();
+        std::os::unix::fs::symlink(sandbox.root.join("sub"), sandbox.root.join("alias")).unwrap();
+        assert!(!sandbox.within("escape/secret.json"));
+        assert!(!sandbox.within("escape/new.json"));
+        assert!(sandbox.within("alias/a.json"));
+    }
+
+    #[test]
+    fn home_paths_are_expanded() {
+        let sandbox = Sandbox::new("home");
+        let Some(home) = home_dir() else {
+            return;
+        };
+        assert_eq!(resolve_path(&sandbox.root, "~"), home);
+        assert_eq!(resolve_path(&sandbox.root, "~/x.json"), home.join("x.json"));
+        assert!(!sandbox.within("~/x.json"));
+        // only a leading `~/` is the home directory
+        assert_eq!(
+            resolve_path(&sandbox.root, "sub/~/x.json"),
+            sandbox.root.join("sub/~/x.json")
+        );
+        assert!(sandbox.within("sub/~/x.json"));
+    }
+
+    #[test]
+    fn directories_are_not_completed_with_the_extension() {
+        let sandbox = Sandbox::new("typed");
+        let json = FileFilter::extensions(&["json"]);
+        let root = &sandbox.root;
+        assert_eq!(typed_path(root, &json, "sub", false), root.join("sub"));
+        assert_eq!(typed_path(root, &json, "sub/", false), root.join("sub/"));
+        assert_eq!(
+            typed_path(root, &json, "newdir/", false),
+            root.join("newdir/")
+        );
+        assert_eq!(
+            typed_path(root, &json, "sub/a", false),
+            root.join("sub/a.json")
+        );
+        assert_eq!(typed_path(root, &json, "new", false), root.join("new.json"));
+    }
+
+    #[test]
+    fn a_missing_root_contains_nothing() {
+        let sandbox = Sandbox::new("noroot");
+        let missing = sandbox.root.join("missing");
+        assert!(!is_within(&missing, &missing.join("x.json")));
+    }
+
+    #[test]
+    fn names_are_completed_with_the_first_extension() {
+        let filter = FileFilter::extensions(&["json", "toml"]);
+        let name = |input: &str, save| filter.complete_name(PathBuf::from(input), save);
+        assert_eq!(name("save", false), Path::new("save.json"));
+        assert_eq!(name("save", true), Path::new("save.json"));
+        assert_eq!(name("save.v2", true), Path::new("save.v2.json"));
+        // opening keeps an existing extension, and matching is case-insensitive
+        assert_eq!(name("save.v2", false), Path::new("save.v2"));
+        assert_eq!(name("x.JSON", true), Path::new("x.JSON"));
+        assert_eq!(name("x.toml", true), Path::new("x.toml"));
+        assert_eq!(
+            FileFilter::All.complete_name(PathBuf::from("notes"), true),
+            Path::new("notes")
+        );
+    }
+
+    #[test]
+    fn numbered_backups_rotate_and_drop_the_oldest() {
+        let sandbox = Sandbox::new("backup");
+        let file = sandbox.root.join("game.json");
+        let backup = |n: usize| with_suffix(&file, &format!(".bak.{}", n));
+        for version in 1..=4 {
+            fs::write(&file, version.to_string()).unwrap();
+            let made = backup_file(&file, BackupPolicy::Numbered(3)).unwrap();
+            assert_eq!(made, Some(backup(1)));
+        }
+        assert_eq!(fs::read_to_string(backup(1)).unwrap(), "4");
+        assert_eq!(fs::read_to_string(backup(2)).unwrap(), "3");
+        assert_eq!(fs::read_to_string(backup(3)).unwrap(), "2");
+        assert!(!backup(4).exists());
+
+        assert_eq!(backup_file(&file, BackupPolicy::None).unwrap(), None);
+        assert_eq!(backup_file(&file, BackupPolicy::Numbered(0)).unwrap(), None);
+        assert_eq!(
+            backup_file(&file, BackupPolicy::Single).unwrap(),
+            Some(with_suffix(&file, ".bak"))
+        );
+    }
+}
@@ -0,0 +1,332 @@
+use crate::{AdvInput, Wizard, WizardAnswers};
+use colored::ColoredString;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A questionnaire described as data (JSON, or TOML with the `toml` feature), so it can be
+/// changed without recompiling. `run()` asks all fields through a `Wizard` and returns the
+/// answers as a JSON object.
+///
+/// ```json
+/// { "title": "New character",
+///   "fields": [
+///     { "name": "name", "type": "string", "default": "John Doe" },
+///     { "name": "level", "type": "int", "min": 1, "max": 20, "help": "Starting level" },
+///     { "name": "class", "type": "choice", "choices": ["Warrior", "Mage"] },
+///     { "name": "school", "type": "choice", "choices": ["Fire", "Ice"],
+///       "when": { "field": "class", "equals": "Mage" } } ] }
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct FormDefinition {
+    #[serde(default)]
+    pub title: Option<String>,
+    pub fields: Vec<FormField>,
+}
+
+/// One field of a `FormDefinition`
+#[derive(Debug, Clone, Deserialize)]
+pub struct FormField {
+    /// key of the answer in the result
+    pub name: String,
+    /// prompt text; defaults to `"<name>: "`
+    #[serde(default)]
+    pub prompt: Option<String>,
+    /// printed (as hint) before the prompt
+    #[serde(default)]
+    pub help: Option<String>,
+    /// pre-fills the input
+    #[serde(default)]
+    pub default: Option<Value>,
+    /// the field is only asked if this condition holds
+    #[serde(default)]
+    pub when: Option<FieldCondition>,
+    #[serde(flatten)]
+    pub kind: FieldKind,
+}
+
+/// The type of a `FormField`, selected by its `"type"` key
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum FieldKind {
+    String,
+    Int {
+        #[serde(default)]
+        min: Option<i64>,
+        #[serde(default)]
+        max: Option<i64>,
+    },
+    Float {
+        #[serde(default)]
+        min: Option<f64>,
+        #[serde(default)]
+        max: Option<f64>,
+    },
+    Choice {
+        choices: Vec<String>,
+    },
+    Bool,
+    File {
+        /// names are resolved relative to this directory
+        #[serde(default)]
+        dir: Option<PathBuf>,
+        /// appended if the typed name has no extension
+        #[serde(default)]
+        extension: Option<String>,
+        #[serde(default)]
+        must_exist: bool,
+    },
+}
+
+/// Condition of a `FormField`, comparing the (typed) answer of an earlier field
+#[derive(Debug, Clone, Deserialize)]
+pub struct FieldCondition {
+    pub field: String,
+    #[serde(default)]
+    pub equals: Option<Value>,
+    #[serde(default)]
+    pub not_equals: Option<Value>,
+    #[serde(default)]
+    pub one_of: Vec<Value>,
+}
+
+/// Errors while loading a form definition or saving its answers
+#[derive(Debug)]
+pub enum FormError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    #[cfg(feature = "toml")]
+    Toml(String),
+    /// the file extension does not name a supported (or enabled) format
+    UnsupportedFormat(PathBuf),
+}
+
+impl fmt::Display for FormError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormError::Io(e) => write!(f, "I/O error: {}", e),
+            FormError::Json(e) => write!(f, "JSON error: {}", e),
+            #[cfg(feature = "toml")]
+            FormError::Toml(e) => write!(f, "TOML error: {}", e),
+            FormError::UnsupportedFormat(p) => {
+                write!(f, "unsupported file format: {}", p.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for FormError {}
+
+impl From<std::io::Error> for FormError {
+    fn from(e: std::io::Error) -> Self {
+        FormError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for FormError {
+    fn from(e: serde_json::Error) -> Self {
+        FormError::Json(e)
+    }
+}
+
+impl FormDefinition {
+    /// parses a definition from JSON text
+    pub fn from_json_str(s: &str) -> Result<Self, FormError> {
+        Ok(serde_json::from_str(s)?)
+    }
+
+    /// parses a definition from TOML text
+    #[cfg(feature = "toml")]
+    pub fn from_toml_str(s: &str) -> Result<Self, FormError> {
+        toml::from_str(s).map_err(|e| FormError::Toml(e.to_string()))
+    }
+
+    /// loads a definition from a `.json` (or `.toml`) file
+    pub fn from_file(path: &Path) -> Result<Self, FormError> {
+        let content = fs::read_to_string(path)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => Self::from_json_str(&content),
+            #[cfg(feature = "toml")]
+            Some("toml") => Self::from_toml_str(&content),
+            _ => Err(FormError::UnsupportedFormat(path.to_path_buf())),
+        }
+    }
+
+    /// asks all fields (with back navigation and summary, see `Wizard`).
+    /// Returns a JSON object with the typed answers of all asked fields, or `None` if the user
+    /// cancelled
+    pub fn run(&self, adv: &mut AdvInput) -> Option<Value> {
+        if let Some(title) = &self.title {
+            println!("{}", adv.out_theme().heading.paint(title));
+        }
+        let mut wizard = Wizard::new();
+        for field in &self.fields {
+            wizard = wizard.step(&field.name, field.prompt_text(), move |adv, p, prev| {
+                field.ask(adv, p, prev)
+            });
+            if let Some(condition) = &field.when {
+                wizard = wizard.when(move |answers| self.holds(condition, answers));
+            }
+        }
+        let answers = wizard.run(adv)?;
+        Some(self.to_value(&answers))
+    }
+
+    /// runs the form and writes the answers to `path` (JSON, or TOML by extension).
+    /// Returns `Ok(None)` if the user cancelled
+    pub fn run_to_file(&self, adv: &mut AdvInput, path: &Path) -> Result<Option<Value>, FormError> {
+        match self.run(adv) {
+            Some(value) => {
+                save_answers(&value, path)?;
+                Ok(Some(value))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn field(&self, name: &str) -> Option<&FormField> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+
+    fn holds(&self, condition: &FieldCondition, answers: &WizardAnswers) -> bool {
+        let value = match (self.field(&condition.field), answers.get(&condition.field)) {
+            (Some(field), Some(answer)) => field.typed_value(answer),
+            // an unanswered (or skipped) field never matches
+            _ => return false,
+        };
+        condition
+            .equals
+            .as_ref()
+            .is_none_or(|v| values_match(v, &value))
+            && condition
+                .not_equals
+                .as_ref()
+                .is_none_or(|v| !values_match(v, &value))
+            && (condition.one_of.is_empty()
+                || condition.one_of.iter().any(|v| values_match(v, &value)))
+    }
+
+    fn to_value(&self, answers: &WizardAnswers) -> Value {
+        let mut map = Map::new();
+        for (name, answer) in answers.iter() {
+            if let Some(field) = self.field(name) {
+                map.insert(name.to_string(), field.typed_value(answer));
+            }
+        }
+        Value::Object(map)
+    }
+}
+
+impl FormField {
+    fn prompt_text(&self) -> ColoredString {
+        match &self.prompt {
+            Some(p) => p.as_str().into(),
+            None => format!("{}: ", self.name).into(),
+        }
+    }
+
+    /* the default as text, the way the user would have typed it */
+    fn default_text(&self) -> Option<String> {
+        match &self.default {
+            Some(Value::Bool(b)) => Some(if *b { "yes" } else { "no" }.to_string()),
+            Some(Value::String(s)) => Some(s.clone()),
+            Some(Value::Null) | None => None,
+            Some(other) => Some(other.to_string()),
+        }
+    }
+
+    fn ask(&self, adv: &mut AdvInput, prompt: ColoredString, prev: Option<&str>) -> Option<String> {
+        if let Some(help) = &self.help {
+            println!("{}", adv.out_theme().hint.paint(help));
+        }
+        let default = self.default_text();
+        let initial = prev.map(str::to_string).or(default);
+        let initial = initial.as_deref();
+        match &self.kind {
+            FieldKind::String => adv.get_string_initial(prompt, initial.unwrap_or("")),
+            FieldKind::Int { min, max } => adv
+                .get_string_initial(prompt, initial.unwrap_or(""))
+                .and_then(|s| s.parse::<i64>().ok())
+                .filter(|v| min.is_none_or(|m| *v >= m) && max.is_none_or(|m| *v <= m))
+                .map(|v| v.to_string()),
+            FieldKind::Float { min, max } => {
+                let initial = initial.and_then(|s| s.parse().ok()).or(*min).unwrap_or(0.0);
+                adv.get_f64_initial(prompt, initial)
+                    .filter(|v| min.is_none_or(|m| *v >= m) && max.is_none_or(|m| *v <= m))
+                    .map(|v| v.to_string())
+            }
+            FieldKind::Choice { choices } => adv.get_choice_input(prompt, choices, initial, true),
+            FieldKind::Bool => {
+                let choices = ["yes".to_string(), "no".to_string()];
+                adv.get_choice_input(prompt, &choices, initial, true)
+            }
+            FieldKind::File {
+                dir,
+                extension,
+                must_exist,
+            } => {
+                let line = adv.get_string_initial(prompt, initial.unwrap_or(""))?;
+                if line.is_empty() {
+                    return None;
+                }
+                let mut path = dir.clone().unwrap_or_default().join(line);
+                if let Some(ext) = extension
+                    && path.extension().is_none()
+                {
+                    path.set_extension(ext);
+                }
+                if *must_exist && !path.exists() {
+                    eprintln!(
+                        "{}",
+                        adv.err_theme()
+                            .warning
+                            .paint(&format!("{} does not exist", path.display()))
+                    );
+                    return None;
+                }
+                Some(path.to_string_lossy().to_string())
+            }
+        }
+    }
+
+    /* converts a (validated) answer to its JSON value */
+    fn typed_value(&self, answer: &str) -> Value {
+        match &self.kind {
+            FieldKind::Int { .. } => answer
+                .parse::<i64>()
+                .map(Value::from)
+                .unwrap_or(Value::Null),
+            FieldKind::Float { .. } => answer
+                .parse::<f64>()
+                .map(Value::from)
+                .unwrap_or(Value::Null),
+            FieldKind::Bool => Value::Bool(answer == "yes"),
+            _ => Value::String(answer.to_string()),
+        }
+    }
+}
+
+/* compares condition values; strings case-insensitively, numbers by value */
+fn values_match(expected: &Value, actual: &Value) -> bool {
+    match (expected, actual) {
+        (Value::String(e), Value::String(a)) => e.eq_ignore_ascii_case(a),
+        (Value::Number(e), Value::Number(a)) => e.as_f64() == a.as_f64(),
+        _ => expected == actual,
+    }
+}
+
+/// writes form answers to `path`, as pretty JSON or (with the `toml` feature) as TOML if the
+/// extension is `.toml`
+pub fn save_answers(answers: &Value, path: &Path) -> Result<(), FormError> {
+    let content = match path.extension().and_then(|e| e.to_str()) {
+        #[cfg(feature = "toml")]
+        Some("toml") => {
+            toml::to_string_pretty(answers).map_err(|e| FormError::Toml(e.to_string()))?
+        }
+        _ => serde_json::to_string_pretty(answers)?,
+    };
+    fs::write(path, content)?;
+    Ok(())
+}
@@ -0,0 +1,374 @@
+use crate::Style;
+use crate::store::{data_file, load_records, save_records};
+use rustyline::{
+    completion::{Completer, Pair},
+    highlight::Highlighter,
+    hint::Hinter,
+    validate::Validator,
+};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::path::PathBuf;
+
+/// how many entries a namespace of a new `PromptHistory` keeps
+pub const DEFAULT_HISTORY_SIZE: usize = 100;
+
+/// Input history of an `AdvInput`, kept separately per prompt id (see `AdvInput::prompt_id`),
+/// so the Up arrow only shows earlier answers to the same question.
+/// With a file set, the history is loaded from it and saved after every new entry
+#[derive(Debug, Clone)]
+pub struct PromptHistory {
+    entries: BTreeMap<String, Vec<String>>,
+    /* how often each entry was given, per prompt */
+    counts: BTreeMap<String, BTreeMap<String, usize>>,
+    file: Option<PathBuf>,
+    max_entries: usize,
+    dedup: bool,
+    ignored: BTreeSet<String>,
+}
+
+impl Default for PromptHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PromptHistory {
+    /// an empty history kept in memory only, with `DEFAULT_HISTORY_SIZE` entries per prompt
+    /// and de-duplication
+    pub fn new() -> Self {
+        PromptHistory {
+            entries: BTreeMap::new(),
+            counts: BTreeMap::new(),
+            file: None,
+            max_entries: DEFAULT_HISTORY_SIZE,
+            dedup: true,
+            ignored: BTreeSet::new(),
+        }
+    }
+
+    /// stores the history in `file` from now on, adding the entries already saved in it
+    /// (a missing file is fine, it is created with the first entry)
+    pub fn set_file(&mut self, file: PathBuf) -> io::Result<()> {
+        for record in load_records(&file)? {
+            if let [id, entry, rest @ ..] = &record[..] {
+                self.push(id, entry.clone());
+                // the count is optional, files without it count each line once
+                if let Some(count) = rest.first().and_then(|c| c.parse().ok())
+                    && let Some(counts) = self.counts.get_mut(id)
+                {
+                    counts.insert(entry.clone(), count);
+                }
+            }
+        }
+        self.file = Some(file);
+        Ok(())
+    }
+
+    /// the history file in the user's data directory (e.g. `~/.local/share/advanced_inputs/`)
+    /// for the application `name`; `None` if there is no data directory
+    pub fn default_file(name: &str) -> Option<PathBuf> {
+        data_file(name, "history")
+    }
+
+    /// keeps at most `max` entries per prompt, dropping the oldest ones
+    pub fn set_max_entries(&mut self, max: usize) {
+        self.max_entries = max;
+        let ids: Vec<String> = self.entries.keys().cloned().collect();
+        for id in ids {
+            self.truncate(&id);
+        }
+    }
+
+    /// with de-duplication (the default), repeating an answer moves it to the end instead of
+    /// adding it again
+    pub fn set_dedup(&mut self, dedup: bool) {
+        self.dedup = dedup;
+    }
+
+    /// never records the answers of prompt `id` (e.g. passwords), and forgets earlier ones
+    pub fn ignore(&mut self, id: &str) {
+        self.entries.remove(id);
+        self.counts.remove(id);
+        self.ignored.insert(id.to_string());
+    }
+
+    pub fn is_ignored(&self, id: &str) -> bool {
+        self.ignored.contains(id)
+    }
+
+    /// the answers given to prompt `id`, oldest first
+    pub fn entries(&self, id: &str) -> &[String] {
+        self.entries.get(id).map_or(&[], |e| e.as_slice())
+    }
+
+    /// the ids of all prompts with history
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+
+    /// records an answer to prompt `id` and saves the history if it has a file.
+    /// Blank answers and ignored prompts are not recorded
+    pub fn add(&mut self, id: &str, entry: &str) -> io::Result<()> {
+        if entry.trim().is_empty() || self.is_ignored(id) {
+            return Ok(());
+        }
+        self.push(id, entry.to_string());
+        self.save()
+    }
+
+    /// how often `entry` was given as answer to prompt `id`
+    pub fn count(&self, id: &str, entry: &str) -> usize {
+        self.counts
+            .get(id)
+            .and_then(|c| c.get(entry))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// the distinct earlier answers to prompt `id` starting with `prefix`, the most frequent
+    /// first (and the more recent one of equally frequent answers)
+    pub fn suggestions(&self, id: &str, prefix: &str) -> Vec<String> {
+        let mut suggestions: Vec<&String> = Vec::new();
+        for entry in self.entries(id).iter().rev() {
+            if entry.starts_with(prefix) && !suggestions.contains(&entry) {
+                suggestions.push(entry);
+            }
+        }
+        // stable, so equally frequent answers stay in order of recency
+        suggestions.sort_by_key(|e| std::cmp::Reverse(self.count(id, e)));
+        suggestions.into_iter().cloned().collect()
+    }
+
+    /// forgets the answers to prompt `id`
+    pub fn clear(&mut self, id: &str) -> io::Result<()> {
+        self.entries.remove(id);
+        self.counts.remove(id);
+        self.save()
+    }
+
+    /// writes the history to its file (if it has one)
+    pub fn save(&self) -> io::Result<()> {
+        let records = self.entries.iter().flat_map(|(id, entries)| {
+            entries.iter().map(move |entry| {
+                vec![id.clone(), entry.clone(), self.count(id, entry).to_string()]
+            })
+        });
+        save_records(self.file.as_deref(), records)
+    }
+
+    fn push(&mut self, id: &str, entry: String) {
+        if self.is_ignored(id) {
+            return;
+        }
+        *self
+            .counts
+            .entry(id.to_string())
+            .or_default()
+            .entry(entry.clone())
+            .or_default() += 1;
+        let entries = self.entries.entry(id.to_string()).or_default();
+        if self.dedup {
+            entries.retain(|e| *e != entry);
+        }
+        entries.push(entry);
+        self.truncate(id);
+    }
+
+    /* drops the oldest entries beyond the limit, and the counts of entries no longer kept */
+    fn truncate(&mut self, id: &str) {
+        let Some(entries) = self.entries.get_mut(id) else {
+            return;
+        };
+        let excess = entries.len().saturating_sub(self.max_entries);
+        entries.drain(..excess);
+        if let Some(counts) = self.counts.get_mut(id) {
+            counts.retain(|entry, _| entries.contains(entry));
+        }
+    }
+}
+
+/// a rustyline helper suggesting earlier answers of a prompt (see `PromptHistory::suggestions`):
+/// Tab completes the line from them, and the most recent answer starting with the typed text
+/// is shown as hint, which the Right arrow accepts
+pub struct HistoryCompleterHelper {
+    /// ranked by frequency and recency
+    ranked: Vec<String>,
+    /// the most recent first
+    recent: Vec<String>,
+    hint_style: Style,
+}
+
+impl HistoryCompleterHelper {
+    pub fn new(history: &PromptHistory, id: &str, hint_style: Style) -> Self {
+        let mut recent: Vec<String> = Vec::new();
+        for entry in history.entries(id).iter().rev() {
+            if !recent.contains(entry) {
+                recent.push(entry.clone());
+            }
+        }
+        HistoryCompleterHelper {
+            ranked: history.suggestions(id, ""),
+            recent,
+            hint_style,
+        }
+    }
+}
+
+impl Completer for HistoryCompleterHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &rustyline::Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        /* the whole line is the answer */
+        let prefix = &line[..pos];
+        let matches = self
+            .ranked
+            .iter()
+            .filter(|e| e.starts_with(prefix))
+            .map(|e| Pair {
+                display: e.clone(),
+                replacement: e.clone(),
+            })
+            .collect();
+        Ok((0, matches))
+    }
+}
+
+impl Highlighter for HistoryCompleterHelper {
+    fn highlight<'l>(&self, line: &'l str, _pos: usize) -> Cow<'l, str> {
+        Cow::Borrowed(line)
+    }
+
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        Cow::Owned(self.hint_style.paint(hint))
+    }
+}
+
+impl Hinter for HistoryCompleterHelper {
+    type Hint = String;
+    fn hint(&self, line: &str, pos: usize, _ctx: &rustyline::Context<'_>) -> Option<Self::Hint> {
+        if line.is_empty() || pos < line.len() {
+            return None;
+        }
+        self.recent
+            .iter()
+            .find(|e| e.len() > line.len() && e.starts_with(line))
+            .map(|e| e[line.len()..].to_string())
+    }
+}
+
+impl Validator for HistoryCompleterHelper {}
+impl rustyline::Helper for HistoryCompleterHelper {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn history_with(id: &str, answers: &[&str]) -> PromptHistory {
+        let mut history = PromptHistory::new();
+        for answer in answers {
+            history.add(id, answer).unwrap();
+        }
+        history
+    }
+
+    #[test]
+    fn repeated_answers_move_to_the_end() {
+        let mut history = history_with("level", &["1", "2", "1"]);
+        assert_eq!(history.entries("level"), ["2", "1"]);
+        assert_eq!(history.count("level", "1"), 2);
+
+        history.set_dedup(false);
+        history.add("level", "2").unwrap();
+        assert_eq!(history.entries("level"), ["2", "1", "2"]);
+        // blank answers are not recorded, and prompts are kept apart
+        history.add("level", "  ").unwrap();
+        assert_eq!(history.entries("level").len(), 3);
+        assert!(history.entries("name").is_empty());
+    }
+
+    #[test]
+    fn max_entries_drops_the_oldest_answers_and_their_counts() {
+        let mut history = history_with("level", &["1", "1", "2", "3"]);
+        history.set_max_entries(2);
+        assert_eq!(history.entries("level"), ["2", "3"]);
+        assert_eq!(history.count("level", "1"), 0);
+        assert_eq!(history.count("level", "3"), 1);
+        history.add("level", "4").unwrap();
+        assert_eq!(history.entries("level"), ["3", "4"]);
+        assert_eq!(history.count("level", "2"), 0);
+    }
+
+    #[test]
+    fn ignored_prompts_are_forgotten_and_not_recorded() {
+        let mut history = history_with("password", &["secret"]);
+        history.add("level", "1").unwrap();
+        history.ignore("password");
+        history.add("password", "hunter2").unwrap();
+        assert!(history.entries("password").is_empty());
+        assert_eq!(history.count("password", "secret"), 0);
+        assert_eq!(history.ids().collect::<Vec<_>>(), ["level"]);
+    }
+
+    #[test]
+    fn set_file_reloads_entries_and_counts() {
+        let dir =
+            std::env::temp_dir().join(format!("advanced_inputs_history_{}", std::process::id()));
+        let file = dir.join("test.history");
+        let mut history = PromptHistory::new();
+        history.set_file(file.clone()).unwrap();
+        for answer in ["a", "b", "a", "a"] {
+            history.add("name", answer).unwrap();
+        }
+        history.add("level", "7").unwrap();
+
+        let mut reloaded = PromptHistory::new();
+        reloaded.set_file(file.clone()).unwrap();
+        assert_eq!(reloaded.entries("name"), ["b", "a"]);
+        assert_eq!(reloaded.count("name", "a"), 3);
+        assert_eq!(reloaded.count("name", "b"), 1);
+        assert_eq!(reloaded.entries("level"), ["7"]);
+
+        // files without counts count every line once
+        fs::write(&file, "name\tc\nname\td\n").unwrap();
+        let mut old = PromptHistory::new();
+        old.set_file(file).unwrap();
+        assert_eq!(old.entries("name"), ["c", "d"]);
+        assert_eq!(old.count("name", "c"), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn suggestions_rank_by_frequency_then_recency() {
+        let history = history_with("city", &["Berlin", "Bern", "Bonn", "Bern", "Basel"]);
+        assert_eq!(
+            history.suggestions("city", "B"),
+            ["Bern", "Basel", "Bonn", "Berlin"]
+        );
+        assert_eq!(history.suggestions("city", "Ber"), ["Bern", "Berlin"]);
+        assert!(history.suggestions("city", "b").is_empty());
+        assert!(history.suggestions("town", "").is_empty());
+    }
+
+    #[test]
+    fn the_helper_completes_ranked_and_hints_the_most_recent() {
+        let history = history_with("city", &["Bern", "Bern", "Berlin"]);
+        let helper = HistoryCompleterHelper::new(&history, "city", Style::default());
+        let rl_history = rustyline::history::DefaultHistory::new();
+        let ctx = rustyline::Context::new(&rl_history);
+
+        let (start, matches) = helper.complete("Be", 2, &ctx).unwrap();
+        let replacements: Vec<&str> = matches.iter().map(|m| m.replacement.as_str()).collect();
+        assert_eq!((start, replacements), (0, vec!["Bern", "Berlin"]));
+        assert_eq!(helper.hint("Be", 2, &ctx).as_deref(), Some("rlin"));
+        assert_eq!(helper.hint("Berlin", 6, &ctx), None);
+        assert_eq!(helper.hint("", 0, &ctx), None);
+    }
+}
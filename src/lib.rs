@@ -2,12 +2,13 @@
 /// for getting user input, based on various result types
 use colored::ColoredString;
 use rustyline::{
+    Editor, EventHandler, KeyEvent,
     completion::{Completer, Pair},
+    config::{ColorMode, Configurer},
     highlight::Highlighter,
     hint::Hinter,
     history::FileHistory,
     validate::Validator,
-    Editor,
 };
 use std::borrow::Cow;
 use std::fs::read_dir;
@@ -17,28 +18,417 @@ pub mod promptable_enum;
 // Re-Use trait for other crates
 pub use crate::promptable_enum::PromptableEnum;
 pub mod file_helper;
+pub use crate::file_helper::{
+    BackupPolicy, DirInputOptions, FileChoice, FileFilter, FileInputOptions, FileMode,
+    FileValidator, SaveOptions,
+};
+pub mod navigation;
+pub use crate::navigation::{BACK_INPUT, Navigation};
+pub mod wizard;
+pub use crate::wizard::{Violation, Wizard, WizardAnswers};
+pub mod menu;
+pub use crate::menu::{Menu, MenuFlow};
+pub mod repl;
+pub use crate::repl::{ArgKind, ArgSpec, CommandArgs, CommandSpec, Repl, ReplCommand, ReplFlow};
+pub mod recent;
+pub use crate::recent::RecentFiles;
+pub mod history;
+pub use crate::history::PromptHistory;
+pub mod sticky;
+mod store;
+pub use crate::sticky::StickyAnswers;
+pub mod enum_usage;
+pub use crate::enum_usage::EnumUsage;
+pub mod builder;
+pub use crate::builder::AdvInputBuilder;
+pub mod theme;
+pub use crate::theme::{Style, Theme, ThemeError};
+pub mod color;
+pub use crate::color::{ColorChoice, Stream};
+// the editor settings of `AdvInputBuilder`
+pub use rustyline::config::{Behavior, BellStyle, CompletionType, EditMode};
+#[cfg(feature = "serde")]
+pub mod form;
+#[cfg(feature = "serde")]
+pub use crate::form::FormDefinition;
+#[cfg(feature = "serde")]
+pub mod deserializer;
+#[cfg(feature = "serde")]
+pub use crate::deserializer::{InteractiveDeserializer, from_prompts};
+#[cfg(feature = "serde")]
+pub mod loader;
 // these helpers are not directly used
-use crate::file_helper::FileCompleterHelper;
+use crate::file_helper::{
+    FileCompleterHelper, backup_file, check_dir_writable, check_writable, file_details, is_within,
+    list_dir, resolve_path, typed_path,
+};
+use crate::history::HistoryCompleterHelper;
+use crate::navigation::{BackKeyHandler, NavigationFlags};
 use crate::promptable_enum::EnumCompleterHelper;
+use crate::repl::CommandCompleterHelper;
 
 pub struct AdvInput {
     ed: Editor<ActiveHelper, FileHistory>,
+    nav_flags: NavigationFlags,
+    navigation: Option<Navigation>,
+    history: PromptHistory,
+    sticky_answers: StickyAnswers,
+    enum_usage: EnumUsage,
+    auto_add_history: bool,
+    theme: Theme,
+    color: ColorChoice,
+    /* whether stdout / stderr get colors, resolved by `set_color` */
+    stdout_color: bool,
+    stderr_color: bool,
+    /* whether `set_color` set the override of `colored`, which is only reset then */
+    overrides_colored: bool,
+    prompt_id: Option<PromptId>,
+}
+
+/* history ids of the library's own questions; their texts contain file paths and would
+otherwise start a new history for every file */
+pub(crate) const CREATE_PROMPT_ID: &str = "advanced_inputs:create";
+pub(crate) const OVERWRITE_PROMPT_ID: &str = "advanced_inputs:overwrite";
+#[cfg(feature = "serde")]
+pub(crate) const RETRY_PROMPT_ID: &str = "advanced_inputs:retry";
+pub(crate) const SUMMARY_PROMPT_ID: &str = "advanced_inputs:summary";
+
+/* the theme while colors are off */
+static PLAIN_THEME: Theme = Theme::plain();
+
+/* the id set by `prompt_id` (or `sticky`), and the prompt it was first used for */
+struct PromptId {
+    id: String,
+    bound: Option<String>,
+    sticky: bool,
+}
+
+impl Default for AdvInput {
+    fn default() -> Self {
+        Self::new()
+    }
 }
-use colored::Colorize;
 
 impl AdvInput {
-    /// initiate a new AdvInput (creates the underlying editor)
+    /// initiate a new AdvInput (creates the underlying editor).
+    /// Panics if the editor cannot be created, see `builder()` for a fallible alternative
     pub fn new() -> Self {
-        let mut editor = Editor::new().expect("Failed to create rustyline editor");
+        Self::builder()
+            .build()
+            .expect("Failed to create rustyline editor")
+    }
+
+    /// configures the editor (key bindings, completion, bell, history, colors) before creating
+    /// the `AdvInput`: `AdvInput::builder().edit_mode(EditMode::Vi).build()?`
+    pub fn builder() -> AdvInputBuilder {
+        AdvInputBuilder::new()
+    }
 
+    /* wraps a configured editor */
+    pub(crate) fn with_editor(mut editor: Editor<ActiveHelper, FileHistory>) -> Self {
         editor.set_helper(Some(ActiveHelper::None));
-        AdvInput { ed: editor }
+        let nav_flags = NavigationFlags::default();
+        editor.bind_sequence(
+            KeyEvent::ctrl('B'),
+            EventHandler::Conditional(Box::new(BackKeyHandler::new(nav_flags.clone()))),
+        );
+        let mut adv = AdvInput {
+            ed: editor,
+            nav_flags,
+            navigation: None,
+            history: PromptHistory::new(),
+            sticky_answers: StickyAnswers::new(),
+            enum_usage: EnumUsage::new(),
+            auto_add_history: true,
+            theme: Theme::default(),
+            color: ColorChoice::Auto,
+            stdout_color: false,
+            stderr_color: false,
+            overrides_colored: false,
+            prompt_id: None,
+        };
+        adv.set_color(ColorChoice::Auto);
+        adv
+    }
+
+    /// the styles of all output, see `Theme`
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    pub fn color(&self) -> ColorChoice {
+        self.color
+    }
+
+    /// `Auto` (default) colors prompts, lists and messages only where the environment allows it
+    /// (see `ColorChoice::enabled_for`), `Always` and `Never` override that. These two also
+    /// apply to everything else printed with `colored`, until `Auto` is set again; an override
+    /// the application set itself is left alone
+    pub fn set_color(&mut self, color: ColorChoice) {
+        self.color = color;
+        self.stdout_color = color.enabled_for(Stream::Stdout);
+        self.stderr_color = color.enabled_for(Stream::Stderr);
+        self.ed.set_color_mode(match color {
+            ColorChoice::Always => ColorMode::Forced,
+            _ if self.stdout_color => ColorMode::Enabled,
+            _ => ColorMode::Disabled,
+        });
+        match color {
+            ColorChoice::Always | ColorChoice::Never => {
+                colored::control::set_override(color == ColorChoice::Always);
+                self.overrides_colored = true;
+            }
+            ColorChoice::Auto if self.overrides_colored => {
+                colored::control::unset_override();
+                self.overrides_colored = false;
+            }
+            ColorChoice::Auto => {}
+        }
+    }
+
+    /* the styles of output to stdout (including prompts and hints) and to stderr */
+    pub(crate) fn out_theme(&self) -> &Theme {
+        if self.stdout_color {
+            &self.theme
+        } else {
+            &PLAIN_THEME
+        }
+    }
+
+    pub(crate) fn err_theme(&self) -> &Theme {
+        if self.stderr_color {
+            &self.theme
+        } else {
+            &PLAIN_THEME
+        }
+    }
+
+    /* the prompt as shown: prompts without own colors get the theme's prompt style */
+    pub(crate) fn prompt_text(&self, prompt: ColoredString) -> String {
+        if !self.stdout_color {
+            strip_ansi(&prompt.to_string())
+        } else if prompt.is_plain() {
+            self.out_theme().prompt.paint(&prompt)
+        } else {
+            prompt.to_string()
+        }
     }
 
     fn reset_helper(&mut self) {
         *self.ed.helper_mut().expect("Helper not set on Editor") = ActiveHelper::None;
     }
 
+    /// enables or disables the navigation inputs (`:back`, Ctrl-B). While enabled, those inputs
+    /// make every `get_*` function return `None` and are reported by `take_navigation()`
+    pub fn set_navigation(&mut self, enabled: bool) {
+        self.nav_flags.set_enabled(enabled);
+        self.navigation = None;
+    }
+
+    /// returns true while the navigation inputs are enabled
+    pub fn navigation_enabled(&self) -> bool {
+        self.nav_flags.is_enabled()
+    }
+
+    /// returns (and clears) the navigation request of the last prompt, if there was one
+    pub fn take_navigation(&mut self) -> Option<Navigation> {
+        self.navigation.take()
+    }
+
+    /* single place where a line is read from the editor; handles the navigation inputs */
+    fn read_line(&mut self, prompt: &str, initial: &str) -> Option<String> {
+        self.navigation = None;
+        let initial = match self.current_prompt_id(prompt) {
+            (id, true) => self.sticky_answers.get(&id).unwrap_or(initial).to_string(),
+            _ => initial.to_string(),
+        };
+        let rl = self.readline_raw(prompt, &initial);
+        let navigating = self.nav_flags.is_enabled();
+        if self.nav_flags.take_back_key() {
+            self.navigation = Some(Navigation::Back);
+            return None;
+        }
+        match rl {
+            Ok(line) => {
+                if navigating && line.trim() == BACK_INPUT {
+                    self.navigation = Some(Navigation::Back);
+                    return None;
+                }
+                Some(line)
+            }
+            Err(_) => {
+                if navigating {
+                    self.navigation = Some(Navigation::Cancel);
+                }
+                None
+            }
+        }
+    }
+
+    /// sets the id of the next prompt, which selects its history (and keeps it apart from the
+    /// answers to other prompts). It stays in effect while the same prompt is repeated, e.g.
+    /// after invalid input. Without an id, the prompt text is used:
+    /// `advi.prompt_id("level").get_index_range("Level: ", 1, 20)`
+    pub fn prompt_id(&mut self, id: &str) -> &mut Self {
+        self.prompt_id = Some(PromptId {
+            id: id.to_string(),
+            bound: None,
+            sticky: false,
+        });
+        self
+    }
+
+    /// like `prompt_id`, but the prompt is also sticky: its last accepted answer is stored
+    /// (see `StickyAnswers`) and pre-fills the input the next time, instead of the given
+    /// initial value: `advi.sticky("level").get_index_initial("Level: ", 1)`
+    pub fn sticky(&mut self, id: &str) -> &mut Self {
+        self.prompt_id(id);
+        if let Some(prompt_id) = &mut self.prompt_id {
+            prompt_id.sticky = true;
+        }
+        self
+    }
+
+    /// the last answers of sticky prompts
+    pub fn sticky_answers(&self) -> &StickyAnswers {
+        &self.sticky_answers
+    }
+
+    /// the last answers of sticky prompts, e.g. to store them in a file
+    pub fn sticky_answers_mut(&mut self) -> &mut StickyAnswers {
+        &mut self.sticky_answers
+    }
+
+    /// how often enum variants were picked, see `EnumUsage`
+    pub fn enum_usage(&self) -> &EnumUsage {
+        &self.enum_usage
+    }
+
+    /// how often enum variants were picked, e.g. to enable ranking or to pin variants
+    pub fn enum_usage_mut(&mut self) -> &mut EnumUsage {
+        &mut self.enum_usage
+    }
+
+    /* the id of the prompt about to be read (see `prompt_id`), and whether it is sticky */
+    fn current_prompt_id(&mut self, prompt: &str) -> (String, bool) {
+        match &mut self.prompt_id {
+            Some(PromptId {
+                id,
+                bound: bound @ None,
+                sticky,
+            }) => {
+                *bound = Some(prompt.to_string());
+                (id.clone(), *sticky)
+            }
+            Some(PromptId {
+                id,
+                bound: Some(bound),
+                sticky,
+            }) if bound == prompt => (id.clone(), *sticky),
+            _ => {
+                self.prompt_id = None;
+                (plain_text(prompt), false)
+            }
+        }
+    }
+
+    /* asks one of the library's own questions under `id`, then restores the caller's prompt id
+    (e.g. for the re-prompt after a declined overwrite) */
+    pub(crate) fn internal_confirmation(
+        &mut self,
+        id: &str,
+        question: impl Into<ColoredString>,
+        default: bool,
+    ) -> Option<bool> {
+        let caller = self.prompt_id.take();
+        let answer = self.prompt_id(id).get_confirmation(question, default);
+        self.prompt_id = caller;
+        answer
+    }
+
+    /* stores an accepted answer if the prompt is sticky */
+    fn remember_answer(&mut self, prompt: &str, answer: &str) {
+        let (id, sticky) = self.current_prompt_id(prompt);
+        if sticky && let Err(e) = self.sticky_answers.set(&id, answer) {
+            eprintln!(
+                "{}",
+                self.err_theme()
+                    .warning
+                    .paint(&format!("Could not save the answer: {}", e))
+            );
+        }
+    }
+
+    /* reads a line and converts it; a converted answer is remembered if the prompt is sticky */
+    fn read_value<T>(
+        &mut self,
+        prompt: &str,
+        initial: &str,
+        convert: impl FnOnce(&str) -> Option<T>,
+    ) -> Option<T> {
+        let line = self.read_line(prompt, initial)?;
+        let value = convert(line.trim())?;
+        self.remember_answer(prompt, line.trim());
+        Some(value)
+    }
+
+    /// the input history, see `PromptHistory`
+    pub fn history(&self) -> &PromptHistory {
+        &self.history
+    }
+
+    /// the input history, to configure its file, size, de-duplication and ignored prompts
+    pub fn history_mut(&mut self) -> &mut PromptHistory {
+        &mut self.history
+    }
+
+    /// replaces the input history
+    pub fn set_history(&mut self, history: PromptHistory) {
+        self.history = history;
+    }
+
+    /// whether answers are added to the history automatically (the default)
+    pub fn set_auto_add_history(&mut self, auto_add: bool) {
+        self.auto_add_history = auto_add;
+    }
+
+    /* reads a line without any interpretation, keeping the error (Ctrl-C vs. Ctrl-D); the
+    editor history holds the answers given to the same prompt id before */
+    pub(crate) fn readline_raw(
+        &mut self,
+        prompt: &str,
+        initial: &str,
+    ) -> rustyline::Result<String> {
+        let (id, _) = self.current_prompt_id(prompt);
+        let _ = self.ed.clear_history();
+        for entry in self.history.entries(&id) {
+            let _ = self.ed.add_history_entry(entry.as_str());
+        }
+        let rl = self.ed.readline_with_initial(prompt, (initial, ""));
+        if let Ok(line) = &rl
+            && self.auto_add_history
+            && !self.nav_flags.back_key_pressed()
+            && !(self.nav_flags.is_enabled() && line.trim() == BACK_INPUT)
+            && let Err(e) = self.history.add(&id, line)
+        {
+            eprintln!(
+                "{}",
+                self.err_theme()
+                    .warning
+                    .paint(&format!("Could not save the history: {}", e))
+            );
+        }
+        rl
+    }
+
+    pub(crate) fn set_active_helper(&mut self, helper: ActiveHelper) {
+        *self.ed.helper_mut().expect("Helper not set on Editor") = helper;
+    }
+
     /// returns either a valid usize, or None
     pub fn get_index(&mut self, prompt: impl Into<ColoredString>) -> Option<usize> {
         self.get_index_initial(prompt, 0)
@@ -51,18 +441,10 @@ impl AdvInput {
         initial: usize,
     ) -> Option<usize> {
         self.reset_helper();
-        let prompt_string = prompt.into().to_string();
-        if let Ok(line) = self
-            .ed
-            .readline_with_initial(prompt_string.as_str(), (&format!("{}", initial), ""))
-        {
-            match line.trim().parse::<usize>() {
-                Ok(idx) => Some(idx),
-                Err(_) => None,
-            }
-        } else {
-            None
-        }
+        let prompt_string = self.prompt_text(prompt.into());
+        self.read_value(&prompt_string, &format!("{}", initial), |line| {
+            line.parse::<usize>().ok()
+        })
     }
 
     /// returns a valid usize in the given range (inclusive), or None
@@ -72,15 +454,13 @@ impl AdvInput {
         low: usize,
         high: usize,
     ) -> Option<usize> {
-        if let Some(idx) = self.get_index(prompt) {
-            if idx >= low && idx <= high {
-                Some(idx)
-            } else {
-                None
-            }
-        } else {
-            None
-        }
+        self.reset_helper();
+        let prompt_string = self.prompt_text(prompt.into());
+        self.read_value(&prompt_string, "0", |line| {
+            line.parse::<usize>()
+                .ok()
+                .filter(|idx| *idx >= low && *idx <= high)
+        })
     }
 
     /// returns either a valid float, or None
@@ -95,18 +475,10 @@ impl AdvInput {
         initial: f64,
     ) -> Option<f64> {
         self.reset_helper();
-        let prompt_string = prompt.into().to_string();
-        if let Ok(line) = self
-            .ed
-            .readline_with_initial(prompt_string.as_str(), (&format!("{}", initial), ""))
-        {
-            match line.trim().parse::<f64>() {
-                Ok(nmb) => Some(nmb),
-                Err(_) => None,
-            }
-        } else {
-            None
-        }
+        let prompt_string = self.prompt_text(prompt.into());
+        self.read_value(&prompt_string, &format!("{}", initial), |line| {
+            line.parse::<f64>().ok()
+        })
     }
 
     /// returns either a valid float in between the range (all inclusive), or None
@@ -117,15 +489,12 @@ impl AdvInput {
         high: f64,
     ) -> Option<f64> {
         self.reset_helper();
-        if let Some(nmb) = self.get_f64(prompt) {
-            if nmb >= low && nmb <= high {
-                Some(nmb)
-            } else {
-                None
-            }
-        } else {
-            None
-        }
+        let prompt_string = self.prompt_text(prompt.into());
+        self.read_value(&prompt_string, "0", |line| {
+            line.parse::<f64>()
+                .ok()
+                .filter(|nmb| *nmb >= low && *nmb <= high)
+        })
     }
 
     /// returns either a valid String, or None
@@ -134,21 +503,40 @@ impl AdvInput {
     }
 
     /// returns either a valid `String`, or `None`; uses the provided `initial` value to pre-fill
-    /// the input
+    /// the input. Earlier answers to the prompt are suggested: Tab completes them (the most
+    /// frequent first), and the most recent match is shown as hint
     pub fn get_string_initial(
         &mut self,
         prompt: impl Into<ColoredString>,
         initial: &str,
     ) -> Option<String> {
+        let prompt_string = self.prompt_text(prompt.into());
+        let (id, _) = self.current_prompt_id(&prompt_string);
+        let helper = HistoryCompleterHelper::new(&self.history, &id, self.out_theme().hint);
+        self.set_active_helper(ActiveHelper::History(helper));
+        self.read_value(&prompt_string, initial, |line| Some(line.to_string()))
+    }
+
+    /// asks a yes/no question; an empty input means `default`.
+    /// Returns `None` if cancelled, and asks again for anything else than y(es)/n(o)
+    pub fn get_confirmation(
+        &mut self,
+        prompt: impl Into<ColoredString>,
+        default: bool,
+    ) -> Option<bool> {
         self.reset_helper();
-        let prompt_string = prompt.into().to_string();
-        if let Ok(line) = self
-            .ed
-            .readline_with_initial(prompt_string.as_str(), (initial, ""))
-        {
-            Some(line.trim().to_string())
-        } else {
-            None
+        let prompt_string = self.prompt_text(prompt.into());
+        loop {
+            let line = self.read_line(&prompt_string, "")?;
+            match line.trim().to_lowercase().as_str() {
+                "" => return Some(default),
+                "y" | "yes" => return Some(true),
+                "n" | "no" => return Some(false),
+                _ => eprintln!(
+                    "{}",
+                    self.err_theme().warning.paint("Please answer yes or no.")
+                ),
+            }
         }
     }
 
@@ -220,162 +608,588 @@ impl AdvInput {
         E: PromptableEnum,
     {
         /* define helper for the Enum */
-        let variants = E::variants_as_strings();
+        let variants = self.enum_usage.ranked::<E>();
         if print_variants {
-            let mut all_variants = if let Some(d) = &default {
-                variants
-                    .iter()
-                    .map(|v| {
-                        if v == &d.display_name() {
-                            let mut temp = v.to_owned();
-                            temp.push_str("(*)");
-                            temp
-                        } else {
-                            v.clone()
-                        }
-                    })
-                    .collect::<Vec<String>>()
-                    .join(", ")
-            } else {
-                variants.join(", ")
-            };
-            // Limit to a length of 100
-            let mut outputs: Vec<String> = Vec::new();
-            loop {
-                if all_variants.len() <= 100 {
-                    outputs.push(all_variants);
-                    break;
-                } else {
-                    /* find the closest whitespace to 100 */
-                    match all_variants[..100].rfind(" ") {
-                        Some(idx) => {
-                            outputs.push(all_variants[..idx].to_string()); // push that part to the ouptut
-                            all_variants = all_variants[(idx + 1)..].to_string();
-                            // shift remaining text
-                        }
-                        None => {
-                            /* take everything */
-                            outputs.push(all_variants);
-                            break;
-                        }
-                    }
-                }
-            }
-            println!("{}", outputs.join("\n").bright_magenta().italic());
+            let default_name = default.as_ref().map(|d| d.display_name());
+            print_variant_list(self.out_theme(), &variants, default_name.as_deref());
         }
         let helper = EnumCompleterHelper::new(variants);
         *self.ed.helper_mut().expect("Helper not set on Editor") = ActiveHelper::Enum(helper);
 
-        let prompt_str = prompt.into().to_string();
+        let prompt_str = self.prompt_text(prompt.into());
         let init = match initial {
             Some(v) => v.display_name(),
             None => "".to_string(),
         };
-        let rl = self
-            .ed
-            .readline_with_initial(prompt_str.as_str(), (init.as_str(), ""));
-        if let Ok(line) = rl {
-            let trimmed_line = line.trim();
-            if trimmed_line.is_empty() {
-                return default;
+        match self.read_line(&prompt_str, &init) {
+            Some(line) => {
+                let trimmed_line = line.trim();
+                if trimmed_line.is_empty() {
+                    return default;
+                }
+                let value = E::from_input_str(trimmed_line);
+                if let Some(v) = &value {
+                    self.remember_answer(&prompt_str, &v.display_name());
+                    if let Err(e) = self.enum_usage.record(v) {
+                        eprintln!(
+                            "{}",
+                            self.err_theme()
+                                .warning
+                                .paint(&format!("Could not save the usage: {}", e))
+                        );
+                    }
+                }
+                value
             }
-            E::from_input_str(trimmed_line)
-        } else {
-            default
+            // a navigation request is not an answer, so the default does not apply
+            None if self.navigation.is_some() => None,
+            None => default,
         }
     }
 
-    /// Prompts the user to select a .json file from the current directory with tab completion
-    /// Returns `Ok(PathBuf)` with the selected filename if valid, `Err(PathBuf)` if filename does
-    /// not yet exist
-    pub fn get_json_file_input(
+    /// Prompts the user to select one of the given `choices` using tab completion, like
+    /// `get_enum_input_initial` does for enums (but with a list known only at runtime).
+    /// Matching is case-insensitive; returns the matching entry of `choices`, or `None`
+    pub fn get_choice_input(
         &mut self,
         prompt: impl Into<ColoredString>,
+        choices: &[String],
+        initial: Option<&str>,
+        print_choices: bool,
+    ) -> Option<String> {
+        if print_choices {
+            print_variant_list(self.out_theme(), choices, None);
+        }
+        let helper = EnumCompleterHelper::new(choices.to_vec());
+        *self.ed.helper_mut().expect("Helper not set on Editor") = ActiveHelper::Enum(helper);
+        let prompt_str = self.prompt_text(prompt.into());
+        self.read_value(&prompt_str, initial.unwrap_or(""), |line| {
+            let line = line.to_lowercase();
+            choices.iter().find(|c| c.to_lowercase() == line).cloned()
+        })
+    }
+
+    /// Prompts the user to select a file from `dir` with tab completion.
+    /// `filter` decides which files are offered and accepted, `options` whether directories and
+    /// hidden files are offered, whether the file has to exist (or must not exist) and which
+    /// file is used for an empty input. Typed names are resolved relative to `dir`; input that
+    /// is not acceptable is reported and asked again.
+    /// With `options.recent`, the recently chosen files are listed first and can be selected by
+    /// their number, and the chosen file is recorded. With `options.confine`, paths leading out
+    /// of `dir` (via `..`, absolute paths or symlinks) are rejected
+    pub fn get_file_input(
+        &mut self,
+        prompt: impl Into<ColoredString>,
+        dir: PathBuf,
+        filter: &FileFilter,
+        options: &FileInputOptions,
+    ) -> FileChoice {
+        self.read_file_choice(prompt.into(), dir, filter, options, false)
+    }
+
+    /* `get_file_input`; with `save`, typed names always get the extension of the filter */
+    fn read_file_choice(
+        &mut self,
+        prompt: ColoredString,
         dir: PathBuf,
-    ) -> Result<PathBuf, PathBuf> {
-        let json_files = read_json_files_in_dir(&dir);
-        if json_files.is_empty() {
+        filter: &FileFilter,
+        options: &FileInputOptions,
+        save: bool,
+    ) -> FileChoice {
+        let files = list_dir(&dir, filter, options);
+        if files.is_empty() {
+            let hint = match options.mode {
+                FileMode::MustExist => "No matching files found",
+                _ => "No matching files found, just enter name for a new one",
+            };
+            eprintln!("{}", self.err_theme().warning.paint(hint));
+        }
+        let helper_instance =
+            FileCompleterHelper::new(dir.clone(), filter.clone(), options.clone());
+        if let Some(helper_ref) = self.ed.helper_mut() {
+            *helper_ref = ActiveHelper::File(Box::new(helper_instance));
+        } else {
             eprintln!(
                 "{}",
-                "No .json files found, just enter name for a new one".yellow()
+                self.err_theme()
+                    .warning
+                    .paint("Internal Error, Tab-completion not available for filenames")
             );
         }
-        let helper_instance = FileCompleterHelper::new(json_files.clone());
-        if let Some(helper_ref) = self.ed.helper_mut() {
-            *helper_ref = ActiveHelper::File(helper_instance);
-        } else {
+        let cps = self.prompt_text(prompt);
+        let recent: Vec<PathBuf> = match (&options.recent, options.mode) {
+            (Some(list), FileMode::MustExist | FileMode::Either) => list
+                .load()
+                .into_iter()
+                .filter(|p| match p.is_dir() {
+                    true => options.include_dirs,
+                    false => filter.matches(p),
+                })
+                .filter(|p| !options.confine || is_within(&dir, p))
+                .collect(),
+            _ => Vec::new(),
+        };
+        if !recent.is_empty() {
+            let abs_dir = std::path::absolute(&dir).unwrap_or_else(|_| dir.clone());
+            println!("recent files:");
+            for (i, path) in recent.iter().enumerate() {
+                let shown = path.strip_prefix(&abs_dir).unwrap_or(path);
+                println!(
+                    "  {} {} {}",
+                    self.out_theme().heading.paint(&format!("{})", i + 1)),
+                    self.out_theme().default_marker.paint("*"),
+                    self.out_theme()
+                        .highlight
+                        .paint(&shown.display().to_string())
+                );
+            }
+        }
+        if !files.is_empty() && options.details {
+            println!("available files:");
+            for name in &files {
+                let path = dir.join(name);
+                let details = file_details(&path, options.preview_key.as_deref());
+                match options.invalid_reason(&path) {
+                    Some(reason) => println!(
+                        "  {}  {}",
+                        self.out_theme().error.paint(name),
+                        self.out_theme()
+                            .hint
+                            .paint(&format!("({}, invalid: {})", details, reason))
+                    ),
+                    None => println!(
+                        "  {}  {}",
+                        self.out_theme().highlight.paint(name),
+                        self.out_theme().hint.paint(&format!("({})", details))
+                    ),
+                }
+            }
+        } else if !files.is_empty() {
+            let names: Vec<String> = files
+                .iter()
+                .map(|name| match options.invalid_reason(&dir.join(name)) {
+                    Some(_) => self.out_theme().error.paint(&format!("{} (invalid)", name)),
+                    None => self.out_theme().highlight.paint(name),
+                })
+                .collect();
+            println!("available files: {}", names.join(", "));
+        }
+        let choice = loop {
+            let Some(line) = self.read_line(&cps, "") else {
+                return FileChoice::Cancelled;
+            };
+            let tl = line.trim();
+            if tl.is_empty() {
+                let Some(name) = &options.default_name else {
+                    return FileChoice::Cancelled;
+                };
+                let default = filter.complete_name(dir.join(name), save);
+                if options.confine && !is_within(&dir, &default) {
+                    eprintln!(
+                        "{}",
+                        self.err_theme().warning.paint(&format!(
+                            "{} is outside of {}, please try again",
+                            name,
+                            dir.display()
+                        ))
+                    );
+                    continue;
+                }
+                match options.invalid_reason(&default) {
+                    Some(reason) => {
+                        eprintln!(
+                            "{}",
+                            self.err_theme().warning.paint(&format!(
+                                "{} is not valid ({}), please try again",
+                                name, reason
+                            ))
+                        );
+                        continue;
+                    }
+                    None => break FileChoice::Default(default),
+                }
+            }
+            // a number selects a recent file, unless a file of that name (e.g. `1.json`) exists
+            let typed = typed_path(&dir, filter, tl, save);
+            let file_path = match tl.parse::<usize>() {
+                Ok(n) if (1..=recent.len()).contains(&n) && !typed.exists() => {
+                    recent[n - 1].clone()
+                }
+                _ => typed,
+            };
+            if options.confine && !is_within(&dir, &file_path) {
+                eprintln!(
+                    "{}",
+                    self.err_theme().warning.paint(&format!(
+                        "{} is outside of {}, please choose a file inside it",
+                        tl,
+                        dir.display()
+                    ))
+                );
+                continue;
+            }
+            let acceptable = if file_path.is_dir() {
+                options.include_dirs
+            } else {
+                filter.matches(&file_path)
+            };
+            let exists = file_path.exists();
+            let problem = match (acceptable, options.mode, exists) {
+                (false, _, _) => "is not a valid choice",
+                (true, FileMode::MustExist, false) => "does not exist",
+                (true, FileMode::MustNotExist, true) => "already exists",
+                (true, _, false) => break FileChoice::New(file_path),
+                _ => match options.invalid_reason(&file_path) {
+                    Some(reason) => {
+                        eprintln!(
+                            "{}",
+                            self.err_theme().warning.paint(&format!(
+                                "{} is not valid ({}), please try again",
+                                tl, reason
+                            ))
+                        );
+                        continue;
+                    }
+                    None => break FileChoice::Existing(file_path),
+                },
+            };
+            eprintln!(
+                "{}",
+                self.err_theme()
+                    .warning
+                    .paint(&format!("{} {}, please try again", tl, problem))
+            );
+        };
+        if let (Some(list), Some(path)) = (&options.recent, choice.path())
+            && let Err(e) = list.record(path)
+        {
             eprintln!(
                 "{}",
-                "Internal Error, Tab-completion not available for filenames".yellow()
+                self.err_theme()
+                    .warning
+                    .paint(&format!("Could not update the recent files: {}", e))
+            );
+        }
+        choice
+    }
+
+    /// Prompts the user to select a .json file from `dir` with tab completion.
+    /// `default` is the file name used for an empty input; without it, an empty input counts as
+    /// cancelled
+    pub fn get_json_file_input(
+        &mut self,
+        prompt: impl Into<ColoredString>,
+        dir: PathBuf,
+        default: Option<&str>,
+    ) -> FileChoice {
+        let filter = FileFilter::extensions(&["json"]);
+        let options = FileInputOptions {
+            default_name: default.map(str::to_string),
+            ..Default::default()
+        };
+        self.get_file_input(prompt, dir, &filter, &options)
+    }
+
+    /// like `get_json_file_input`, but lists the files newest first with their size and
+    /// modification time, and the value of `preview_key` (e.g. `"name"`) in each file, so save
+    /// slots can be told apart
+    pub fn get_json_file_input_detailed(
+        &mut self,
+        prompt: impl Into<ColoredString>,
+        dir: PathBuf,
+        default: Option<&str>,
+        preview_key: Option<&str>,
+    ) -> FileChoice {
+        let filter = FileFilter::extensions(&["json"]);
+        let options = FileInputOptions {
+            default_name: default.map(str::to_string),
+            details: true,
+            preview_key: preview_key.map(str::to_string),
+            ..Default::default()
+        };
+        self.get_file_input(prompt, dir, &filter, &options)
+    }
+
+    /// Prompts the user to select a directory below `base` with tab completion (offering
+    /// directories only). A missing directory can be created after confirmation if
+    /// `options.create_missing` is set; the chosen directory must be readable (and writable, if
+    /// `options.require_writable` is set).
+    /// Returns `FileChoice::Existing`, `New` (just created), `Default` or `Cancelled`
+    pub fn get_dir_input(
+        &mut self,
+        prompt: impl Into<ColoredString>,
+        base: PathBuf,
+        options: &DirInputOptions,
+    ) -> FileChoice {
+        let file_options = FileInputOptions {
+            include_dirs: true,
+            show_hidden: options.show_hidden,
+            ..Default::default()
+        };
+        let dirs = list_dir(&base, &FileFilter::NoFiles, &file_options);
+        if !dirs.is_empty() {
+            println!(
+                "available directories: {}",
+                self.out_theme().highlight.paint(&dirs.join(", "))
             );
         }
-        let cps = prompt.into().to_string();
-        if !json_files.is_empty() {
-            println!("available files: {}", json_files.join(", ").cyan());
+        let prompt_string = self.prompt_text(prompt.into());
+        loop {
+            self.set_active_helper(ActiveHelper::File(Box::new(FileCompleterHelper::new(
+                base.clone(),
+                FileFilter::NoFiles,
+                file_options.clone(),
+            ))));
+            let Some(line) = self.read_line(&prompt_string, "") else {
+                return FileChoice::Cancelled;
+            };
+            let tl = line.trim();
+            let (path, is_default) = match (tl.is_empty(), &options.default_name) {
+                (false, _) => (resolve_path(&base, tl), false),
+                (true, Some(name)) => (base.join(name), true),
+                (true, None) => return FileChoice::Cancelled,
+            };
+            let mut created = false;
+            if !path.exists() {
+                if !options.create_missing {
+                    eprintln!(
+                        "{}",
+                        self.err_theme()
+                            .warning
+                            .paint(&format!("{} does not exist", path.display()))
+                    );
+                    continue;
+                }
+                let question = format!("{} does not exist, create it? (y/n) ", path.display());
+                match self.internal_confirmation(CREATE_PROMPT_ID, question, true) {
+                    Some(true) => {}
+                    Some(false) => continue,
+                    None => return FileChoice::Cancelled,
+                }
+                if let Err(e) = std::fs::create_dir_all(&path) {
+                    eprintln!(
+                        "{}: Failed to create directory: {}",
+                        self.err_theme().error.paint("Error"),
+                        e
+                    );
+                    continue;
+                }
+                println!(
+                    "{}",
+                    self.out_theme()
+                        .success
+                        .paint(&format!("created {}", path.display()))
+                );
+                created = true;
+            }
+            let problem = if !path.is_dir() {
+                Some("is not a directory".to_string())
+            } else if let Err(e) = read_dir(&path) {
+                Some(format!("is not readable ({})", e))
+            } else if options.require_writable
+                && let Err(e) = check_dir_writable(&path)
+            {
+                Some(format!("is not writable ({})", e))
+            } else {
+                None
+            };
+            match problem {
+                Some(p) => eprintln!(
+                    "{}",
+                    self.err_theme()
+                        .warning
+                        .paint(&format!("{} {}", path.display(), p))
+                ),
+                None if is_default => return FileChoice::Default(path),
+                None if created => return FileChoice::New(path),
+                None => return FileChoice::Existing(path),
+            }
         }
-        let rl = self.ed.readline(cps.as_str());
-        match rl {
-            Ok(line) => {
-                let tl = line.trim();
-                if tl.is_empty() {
-                    return Err(get_default_file(dir));
+    }
+
+    /// Prompts for a file to save to ("Save As"): `extension` is appended to names without one,
+    /// an existing file is only accepted after confirmation (and then backed up according to
+    /// `options.backup`), and the directory must be writable.
+    /// Returns `FileChoice::Existing` for a confirmed overwrite, `New`, `Default` or `Cancelled`
+    pub fn get_save_file_input(
+        &mut self,
+        prompt: impl Into<ColoredString>,
+        dir: PathBuf,
+        extension: &str,
+        options: &SaveOptions,
+    ) -> FileChoice {
+        let prompt = prompt.into();
+        let filter = FileFilter::extensions(&[extension]);
+        let file_options = FileInputOptions {
+            default_name: options.default_name.clone(),
+            ..Default::default()
+        };
+        loop {
+            let choice =
+                self.read_file_choice(prompt.clone(), dir.clone(), &filter, &file_options, true);
+            let Some(path) = choice.path() else {
+                return choice;
+            };
+            if let Err(e) = check_writable(path) {
+                eprintln!(
+                    "{}",
+                    self.err_theme().warning.paint(&format!(
+                        "Cannot write {}: {}",
+                        path.display(),
+                        e
+                    ))
+                );
+                continue;
+            }
+            if path.exists() {
+                let question = format!("{} exists, overwrite? (y/n) ", path.display());
+                let question = self.out_theme().warning.paint(&question);
+                match self.internal_confirmation(OVERWRITE_PROMPT_ID, question, false) {
+                    Some(true) => {}
+                    Some(false) => continue,
+                    None => return FileChoice::Cancelled,
                 }
-                let file_path = PathBuf::from(tl);
-                if file_path.exists() {
-                    return Ok(file_path);
+                match backup_file(path, options.backup) {
+                    Ok(Some(backup)) => println!(
+                        "{}",
+                        self.out_theme()
+                            .success
+                            .paint(&format!("backup: {}", backup.display()))
+                    ),
+                    Ok(None) => {}
+                    Err(e) => {
+                        eprintln!(
+                            "{}: Backup failed: {}",
+                            self.err_theme().error.paint("Error"),
+                            e
+                        );
+                        continue;
+                    }
                 }
-                return Err(file_path);
             }
-            _ => {
-                return Err(get_default_file(dir));
+            return choice;
+        }
+    }
+}
+
+const DEFAULT_MARKER: &str = "(*)";
+
+/* prints the variants as comma separated list (wrapped at 100 chars), marking the default */
+pub(crate) fn print_variant_list(theme: &Theme, variants: &[String], default: Option<&str>) {
+    let mut all_variants = if let Some(d) = default {
+        variants
+            .iter()
+            .map(|v| {
+                if v == d {
+                    let mut temp = v.to_owned();
+                    temp.push_str(DEFAULT_MARKER);
+                    temp
+                } else {
+                    v.clone()
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(", ")
+    } else {
+        variants.join(", ")
+    };
+    // Limit to a length of 100
+    let mut outputs: Vec<String> = Vec::new();
+    loop {
+        if all_variants.len() <= 100 {
+            outputs.push(all_variants);
+            break;
+        } else {
+            /* find the closest whitespace to 100 (by byte offset, but on a char boundary) */
+            let space = all_variants
+                .char_indices()
+                .take_while(|(i, _)| *i <= 100)
+                .filter(|(_, c)| *c == ' ')
+                .map(|(i, _)| i)
+                .last();
+            match space {
+                Some(idx) => {
+                    outputs.push(all_variants[..idx].to_string()); // push that part to the ouptut
+                    all_variants = all_variants[(idx + 1)..].to_string();
+                    // shift remaining text
+                }
+                None => {
+                    /* take everything */
+                    outputs.push(all_variants);
+                    break;
+                }
             }
         }
     }
+    let marker = theme.default_marker.paint(DEFAULT_MARKER);
+    let lines: Vec<String> = outputs
+        .iter()
+        .map(|line| {
+            line.split(DEFAULT_MARKER)
+                .map(|part| theme.variant_list.paint(part))
+                .collect::<Vec<String>>()
+                .join(&marker)
+        })
+        .collect();
+    println!("{}", lines.join("\n"));
 }
 
-fn get_default_file(dir: PathBuf) -> PathBuf {
-    eprintln!(
-        "{}",
-        "Problem with given filename. John Doe will be  used.".yellow()
-    );
-    let mut file_path = dir.clone();
-    file_path.push("JohnDoe.json");
-    file_path
+/* the text of a prompt without color codes and surrounding whitespace */
+fn plain_text(prompt: &str) -> String {
+    strip_ansi(prompt).trim().to_string()
 }
 
-pub fn read_json_files_in_dir(dir: &PathBuf) -> Vec<String> {
-    let mut json_files = Vec::new();
-    match read_dir(dir) {
-        Ok(entries) => {
-            for entry in entries {
-                if let Ok(entry) = entry {
-                    let path = entry.path();
-                    if path.is_file() {
-                        if let Some(extension) = path.extension() {
-                            if extension == "json" {
-                                if let Some(file_name) = path.file_name() {
-                                    if let Some(s) = file_name.to_str() {
-                                        json_files.push(s.to_string());
-                                    }
-                                }
-                            }
-                        }
-                    }
+/* the text without color codes, e.g. to show a colored prompt while colors are off */
+fn strip_ansi(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // skip the escape sequence up to its final letter
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
                 }
             }
-        }
-        Err(e) => {
-            eprintln!("{}: Failed to read directory: {}", "Error".red(), e);
+        } else {
+            plain.push(c);
         }
     }
-    json_files
+    plain
+}
+
+/* the styles of stderr output of free functions, which have no `AdvInput` to ask */
+fn error_theme() -> &'static Theme {
+    static DEFAULT_THEME: Theme = Theme::default_theme();
+    if ColorChoice::Auto.enabled_for(Stream::Stderr) {
+        &DEFAULT_THEME
+    } else {
+        &PLAIN_THEME
+    }
+}
+
+/// returns the names of the .json files in `dir` (see `file_helper::list_dir`), reporting a
+/// directory that cannot be read
+pub fn read_json_files_in_dir(dir: &PathBuf) -> Vec<String> {
+    if let Err(e) = read_dir(dir) {
+        eprintln!(
+            "{}: Failed to read directory: {}",
+            error_theme().error.paint("Error"),
+            e
+        );
+        return Vec::new();
+    }
+    let filter = FileFilter::extensions(&["json"]);
+    list_dir(dir, &filter, &FileInputOptions::default())
 }
 
 /* concrete helper type for Dynamic Behaviour */
 pub enum ActiveHelper {
     None,
     Enum(EnumCompleterHelper),
-    File(FileCompleterHelper),
+    File(Box<FileCompleterHelper>),
+    Command(CommandCompleterHelper),
+    History(HistoryCompleterHelper),
 }
 
 /* blanket implementation for rustyline's Helper  */
@@ -393,6 +1207,8 @@ impl Completer for ActiveHelper {
             ActiveHelper::None => Ok((pos, vec![])), // no completions
             ActiveHelper::Enum(helper) => helper.complete(line, pos, ctx),
             ActiveHelper::File(helper) => helper.complete(line, pos, ctx),
+            ActiveHelper::Command(helper) => helper.complete(line, pos, ctx),
+            ActiveHelper::History(helper) => helper.complete(line, pos, ctx),
         }
     }
 }
@@ -402,6 +1218,15 @@ impl Highlighter for ActiveHelper {
             ActiveHelper::None => Cow::Borrowed(line),
             ActiveHelper::Enum(helper) => helper.highlight(line, pos),
             ActiveHelper::File(helper) => helper.highlight(line, pos),
+            ActiveHelper::Command(helper) => helper.highlight(line, pos),
+            ActiveHelper::History(helper) => helper.highlight(line, pos),
+        }
+    }
+
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        match self {
+            ActiveHelper::History(helper) => helper.highlight_hint(hint),
+            _ => Cow::Borrowed(hint),
         }
     }
 }
@@ -412,6 +1237,8 @@ impl Hinter for ActiveHelper {
             ActiveHelper::None => None,
             ActiveHelper::Enum(helper) => helper.hint(line, pos, ctx),
             ActiveHelper::File(helper) => helper.hint(line, pos, ctx),
+            ActiveHelper::Command(helper) => helper.hint(line, pos, ctx),
+            ActiveHelper::History(helper) => helper.hint(line, pos, ctx),
         }
     }
 }
@@ -424,6 +1251,23 @@ impl Validator for ActiveHelper {
             ActiveHelper::None => Ok(rustyline::validate::ValidationResult::Valid(None)),
             ActiveHelper::Enum(helper) => helper.validate(ctx),
             ActiveHelper::File(helper) => helper.validate(ctx),
+            ActiveHelper::Command(helper) => helper.validate(ctx),
+            ActiveHelper::History(helper) => helper.validate(ctx),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_send<T: Send>() {}
+
+    #[test]
+    fn adv_input_can_move_to_another_thread() {
+        is_send::<AdvInput>();
+        is_send::<FileFilter>();
+        is_send::<FileInputOptions>();
+        is_send::<ArgSpec>();
+    }
+}
@@ -0,0 +1,147 @@
+use crate::{AdvInput, FileChoice, FileFilter, FileInputOptions, FileMode, RETRY_PROMPT_ID};
+use colored::ColoredString;
+use serde::de::DeserializeOwned;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A file that could not be read or parsed, with the position of the problem if known
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl ParseError {
+    fn new(message: impl fmt::Display) -> Self {
+        ParseError {
+            line: None,
+            column: None,
+            message: message.to_string(),
+        }
+    }
+
+    fn at(line: usize, column: usize, message: impl fmt::Display) -> Self {
+        ParseError {
+            line: Some(line),
+            column: Some(column),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => {
+                write!(f, "line {}, column {}: {}", line, column, self.message)
+            }
+            _ => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// the extensions `load_file` can parse with the enabled features
+pub fn supported_extensions() -> Vec<&'static str> {
+    let mut extensions = vec!["json"];
+    if cfg!(feature = "toml") {
+        extensions.push("toml");
+    }
+    if cfg!(feature = "yaml") {
+        extensions.extend(["yaml", "yml"]);
+    }
+    if cfg!(feature = "ron") {
+        extensions.push("ron");
+    }
+    extensions
+}
+
+/// reads the file at `path` and deserializes it, choosing the format by its extension
+pub fn parse_file<T: DeserializeOwned>(path: &Path) -> Result<T, ParseError> {
+    let content = fs::read_to_string(path).map_err(ParseError::new)?;
+    // the file picker offers extensions case-insensitively, so `DATA.JSON` is JSON, too
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_lowercase);
+    match extension.as_deref() {
+        Some("json") => {
+            serde_json::from_str(&content).map_err(|e| ParseError::at(e.line(), e.column(), e))
+        }
+        #[cfg(feature = "toml")]
+        Some("toml") => toml::from_str(&content).map_err(|e| match e.span() {
+            Some(span) => {
+                let (line, column) = line_column(&content, span.start);
+                ParseError::at(line, column, e.message())
+            }
+            None => ParseError::new(e.message()),
+        }),
+        #[cfg(feature = "yaml")]
+        Some("yaml") | Some("yml") => {
+            serde_yaml::from_str(&content).map_err(|e| match e.location() {
+                Some(loc) => ParseError::at(loc.line(), loc.column(), &e),
+                None => ParseError::new(&e),
+            })
+        }
+        #[cfg(feature = "ron")]
+        Some("ron") => ron::from_str(&content)
+            .map_err(|e| ParseError::at(e.span.start.line, e.span.start.col, &e.code)),
+        _ => Err(ParseError::new(format!(
+            "unsupported file type, expected one of: {}",
+            supported_extensions().join(", ")
+        ))),
+    }
+}
+
+/* 1-based line and column of a byte offset */
+#[cfg(feature = "toml")]
+fn line_column(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rfind('\n')
+        .map_or(before.len(), |i| before.len() - i - 1)
+        + 1;
+    (line, column)
+}
+
+impl AdvInput {
+    /// Prompts the user to select a file from `dir` (see `get_file_input`) and deserializes it
+    /// as `T`: JSON, plus TOML, YAML and RON with the `toml`, `yaml` and `ron` features.
+    /// If the file cannot be parsed, the error is shown with its line and column and the user
+    /// can pick another file.
+    /// Returns the value and its path, or `None` if the user cancelled
+    pub fn load_file<T: DeserializeOwned>(
+        &mut self,
+        prompt: impl Into<ColoredString>,
+        dir: PathBuf,
+    ) -> Option<(T, PathBuf)> {
+        let prompt = prompt.into();
+        let filter = FileFilter::extensions(&supported_extensions());
+        let options = FileInputOptions {
+            mode: FileMode::MustExist,
+            ..Default::default()
+        };
+        loop {
+            let path = match self.get_file_input(prompt.clone(), dir.clone(), &filter, &options) {
+                FileChoice::Cancelled => return None,
+                choice => choice.into_path()?,
+            };
+            match parse_file::<T>(&path) {
+                Ok(value) => return Some((value, path)),
+                Err(e) => eprintln!(
+                    "{}: {}: {}",
+                    self.err_theme().error.paint("Error"),
+                    path.display(),
+                    e
+                ),
+            }
+            if !self.internal_confirmation(RETRY_PROMPT_ID, "Pick another file? (y/n) ", true)? {
+                return None;
+            }
+        }
+    }
+}
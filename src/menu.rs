@@ -0,0 +1,172 @@
+use crate::{AdvInput, Navigation, PromptableEnum, print_variant_list};
+use colored::ColoredString;
+
+/// What a menu handler wants to happen next
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuFlow {
+    /// show the same menu again
+    Stay,
+    /// leave this menu and return to the parent menu (ends `run()` for the top menu)
+    Back,
+    /// leave all menus
+    Quit,
+}
+
+/* submenus have their own enum type, so they are stored as trait objects */
+trait MenuNode {
+    fn run_nested(&mut self, adv: &mut AdvInput, crumbs: &mut Vec<String>) -> MenuFlow;
+}
+
+enum MenuEntry<'a> {
+    Handler(Box<dyn FnMut(&mut AdvInput) -> MenuFlow + 'a>),
+    Submenu(Box<dyn MenuNode + 'a>),
+    Exit,
+}
+
+/// A menu loop for a `PromptableEnum`: each variant maps to a handler, a submenu or exit.
+/// The prompt shows breadcrumbs (`Main > Settings > `), `back` or `..` returns to the parent
+/// menu and `quit` leaves all menus. The loop runs until an exit variant is chosen or a
+/// handler returns `MenuFlow::Back` / `MenuFlow::Quit`.
+///
+/// ```ignore
+/// Menu::<MainMenu>::new("Main")
+///     .handler(MainMenu::LoadFromFile, |adv| { load(adv); MenuFlow::Stay })
+///     .submenu(MainMenu::Settings, Menu::<SettingsMenu>::new("Settings").exit(SettingsMenu::Done))
+///     .exit(MainMenu::Exit)
+///     .run(&mut advi);
+/// ```
+pub struct Menu<'a, E: PromptableEnum> {
+    title: String,
+    entries: Vec<(E, MenuEntry<'a>)>,
+}
+
+const BACK_WORDS: [&str; 2] = ["back", ".."];
+const QUIT_WORD: &str = "quit";
+
+impl<'a, E: PromptableEnum> Menu<'a, E> {
+    /// creates a menu; `title` is its part of the breadcrumbs
+    pub fn new(title: &str) -> Self {
+        Menu {
+            title: title.to_string(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// runs `handler` when `variant` is chosen
+    pub fn handler<F>(mut self, variant: E, handler: F) -> Self
+    where
+        F: FnMut(&mut AdvInput) -> MenuFlow + 'a,
+    {
+        self.entries
+            .push((variant, MenuEntry::Handler(Box::new(handler))));
+        self
+    }
+
+    /// opens `submenu` when `variant` is chosen
+    pub fn submenu<S: PromptableEnum>(mut self, variant: E, submenu: Menu<'a, S>) -> Self {
+        self.entries
+            .push((variant, MenuEntry::Submenu(Box::new(submenu))));
+        self
+    }
+
+    /// leaves this menu when `variant` is chosen (like `back`)
+    pub fn exit(mut self, variant: E) -> Self {
+        self.entries.push((variant, MenuEntry::Exit));
+        self
+    }
+
+    /// runs the menu loop until it is left
+    pub fn run(&mut self, adv: &mut AdvInput) {
+        let mut crumbs = Vec::new();
+        self.run_nested(adv, &mut crumbs);
+    }
+
+    fn entry_mut(&mut self, variant: &E) -> Option<&mut MenuEntry<'a>> {
+        let name = variant.display_name();
+        self.entries
+            .iter_mut()
+            .find(|(v, _)| v.display_name() == name)
+            .map(|(_, e)| e)
+    }
+
+    /* reads one choice; `None` means "go back", built-in words are handled here */
+    fn choose(&mut self, adv: &mut AdvInput, crumbs: &[String]) -> Result<Option<E>, MenuFlow> {
+        let variants = adv.enum_usage().ranked::<E>();
+        print_variant_list(adv.out_theme(), &variants, None);
+        let mut choices = variants;
+        choices.extend(BACK_WORDS.iter().map(|w| w.to_string()));
+        choices.push(QUIT_WORD.to_string());
+        let prompt = ColoredString::from(format!("{} > ", crumbs.join(" > ")));
+        let was_enabled = adv.navigation_enabled();
+        adv.set_navigation(true);
+        let choice = adv.get_choice_input(prompt, &choices, None, false);
+        let navigation = adv.take_navigation();
+        adv.set_navigation(was_enabled);
+        match (choice, navigation) {
+            (Some(c), _) if BACK_WORDS.contains(&c.as_str()) => Err(MenuFlow::Back),
+            (Some(c), _) if c == QUIT_WORD => Err(MenuFlow::Quit),
+            (Some(c), _) => {
+                let variant = E::from_input_str(&c);
+                if let Some(v) = &variant
+                    && let Err(e) = adv.enum_usage_mut().record(v)
+                {
+                    eprintln!(
+                        "{}",
+                        adv.err_theme()
+                            .warning
+                            .paint(&format!("Could not save the usage: {}", e))
+                    );
+                }
+                Ok(variant)
+            }
+            (None, Some(Navigation::Back)) => Err(MenuFlow::Back),
+            (None, Some(Navigation::Cancel)) => Err(MenuFlow::Quit),
+            (None, None) => Ok(None),
+        }
+    }
+}
+
+impl<E: PromptableEnum> MenuNode for Menu<'_, E> {
+    fn run_nested(&mut self, adv: &mut AdvInput, crumbs: &mut Vec<String>) -> MenuFlow {
+        crumbs.push(self.title.clone());
+        let flow = loop {
+            let variant = match self.choose(adv, crumbs) {
+                Ok(Some(v)) => v,
+                Ok(None) => {
+                    eprintln!(
+                        "{}",
+                        adv.err_theme().warning.paint(&format!(
+                            "Unknown choice ({}/.. to go back, {} to leave)",
+                            BACK_WORDS[0], QUIT_WORD
+                        ))
+                    );
+                    continue;
+                }
+                Err(flow) => break flow,
+            };
+            let flow = match self.entry_mut(&variant) {
+                Some(MenuEntry::Handler(handler)) => handler(adv),
+                // returning from a submenu shows this menu again
+                Some(MenuEntry::Submenu(sub)) => match sub.run_nested(adv, crumbs) {
+                    MenuFlow::Quit => MenuFlow::Quit,
+                    _ => MenuFlow::Stay,
+                },
+                Some(MenuEntry::Exit) => MenuFlow::Back,
+                None => {
+                    eprintln!(
+                        "{}",
+                        adv.err_theme()
+                            .warning
+                            .paint(&format!("{} is not available here", variant.display_name()))
+                    );
+                    MenuFlow::Stay
+                }
+            };
+            if flow != MenuFlow::Stay {
+                break flow;
+            }
+        };
+        crumbs.pop();
+        flow
+    }
+}
@@ -0,0 +1,71 @@
+use rustyline::{Cmd, ConditionalEventHandler, Event, EventContext, RepeatCount};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// reserved input that asks a running wizard (or menu) to go back one step
+pub const BACK_INPUT: &str = ":back";
+
+/// what the user asked for instead of answering the last prompt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Navigation {
+    /// `:back` or Ctrl-B: return to the previous step
+    Back,
+    /// Ctrl-C or Ctrl-D: leave the whole flow
+    Cancel,
+}
+
+/* shared flags between AdvInput and the key handler bound to Ctrl-B */
+#[derive(Clone, Default)]
+pub(crate) struct NavigationFlags {
+    enabled: Arc<AtomicBool>,
+    back_key: Arc<AtomicBool>,
+}
+
+impl NavigationFlags {
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// returns true if Ctrl-B accepted the last line, without clearing the flag
+    pub(crate) fn back_key_pressed(&self) -> bool {
+        self.back_key.load(Ordering::Relaxed)
+    }
+
+    /// returns true if Ctrl-B accepted the last line, and clears the flag
+    pub(crate) fn take_back_key(&self) -> bool {
+        self.back_key.swap(false, Ordering::Relaxed)
+    }
+}
+
+/// accepts the line on Ctrl-B while navigation is enabled; otherwise Ctrl-B keeps its default
+/// meaning (backward-char in emacs mode)
+pub(crate) struct BackKeyHandler {
+    flags: NavigationFlags,
+}
+
+impl BackKeyHandler {
+    pub(crate) fn new(flags: NavigationFlags) -> Self {
+        BackKeyHandler { flags }
+    }
+}
+
+impl ConditionalEventHandler for BackKeyHandler {
+    fn handle(
+        &self,
+        _evt: &Event,
+        _n: RepeatCount,
+        _positive: bool,
+        _ctx: &EventContext,
+    ) -> Option<Cmd> {
+        if self.flags.is_enabled() {
+            self.flags.back_key.store(true, Ordering::Relaxed);
+            Some(Cmd::AcceptLine)
+        } else {
+            None
+        }
+    }
+}
@@ -18,7 +18,7 @@ pub fn add_spaces_before_caps(s: &str) -> String {
         // lowercase
         if c.is_uppercase()
             && !result.is_empty()
-            && chars.peek().map_or(false, |&next_c| next_c.is_lowercase())
+            && chars.peek().is_some_and(|&next_c| next_c.is_lowercase())
         {
             result.push(' ');
         }
@@ -59,6 +59,16 @@ pub trait PromptableEnum:
     fn variants_as_strings() -> Vec<String> {
         Self::iter().map(|v| v.display_name()).collect()
     }
+
+    /// the key the picks of this enum are saved under (see `EnumUsage`); by default its
+    /// variant names, so it survives moving the enum. Override it with a fixed name if another
+    /// enum has the same variants, or to keep the counts when variants are added
+    fn usage_key() -> String {
+        Self::iter()
+            .map(|v| format!("{:?}", v))
+            .collect::<Vec<String>>()
+            .join(",")
+    }
 }
 
 /// a rustyline helper that provides tab completion for `PromptableEnum` variants.
@@ -0,0 +1,55 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A list of recently chosen files, persisted in a small state file (one path per line).
+/// Set it as `FileInputOptions::recent` to show the list first in `AdvInput::get_file_input`
+/// and to record the chosen files
+#[derive(Debug, Clone)]
+pub struct RecentFiles {
+    file: PathBuf,
+    max: usize,
+}
+
+impl RecentFiles {
+    /// the list `name` in the user's config directory (e.g. `~/.config/advanced_inputs/`),
+    /// keeping up to `max` entries. Returns `None` if there is no config directory
+    pub fn new(name: &str, max: usize) -> Option<Self> {
+        let dir = dirs::config_dir()?.join("advanced_inputs");
+        Some(Self::at(dir.join(format!("{}.recent", name)), max))
+    }
+
+    /// the list stored in `file`, keeping up to `max` entries
+    pub fn at(file: PathBuf, max: usize) -> Self {
+        RecentFiles { file, max }
+    }
+
+    /// the recent files, most recent first; files that no longer exist are left out
+    pub fn load(&self) -> Vec<PathBuf> {
+        let content = fs::read_to_string(&self.file).unwrap_or_default();
+        content
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(PathBuf::from)
+            .filter(|p| p.exists())
+            .take(self.max)
+            .collect()
+    }
+
+    /// moves `path` to the front of the list, drops missing files and writes the list back
+    pub fn record(&self, path: &Path) -> io::Result<()> {
+        let path = std::path::absolute(path)?;
+        let mut entries = self.load();
+        entries.retain(|p| *p != path);
+        entries.insert(0, path);
+        entries.truncate(self.max);
+        if let Some(dir) = self.file.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let lines: Vec<String> = entries
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+        fs::write(&self.file, lines.join("\n") + "\n")
+    }
+}
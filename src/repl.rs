@@ -0,0 +1,1020 @@
+use crate::file_helper::{FileCompleterHelper, FileFilter, FileInputOptions};
+use crate::{ActiveHelper, AdvInput, PromptableEnum, Theme};
+use colored::ColoredString;
+use rustyline::{
+    completion::{Completer, Pair},
+    error::ReadlineError,
+    highlight::Highlighter,
+    hint::Hinter,
+    validate::Validator,
+};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+const HELP_COMMAND: &str = "help";
+const EXIT_COMMANDS: [&str; 2] = ["exit", "quit"];
+const SOURCE_COMMAND: &str = "source";
+const ALIAS_COMMAND: &str = "alias";
+const UNALIAS_COMMAND: &str = "unalias";
+const SET_COMMAND: &str = "set";
+const UNSET_COMMAND: &str = "unset";
+const COMMENT: char = '#';
+/* protects against scripts sourcing themselves */
+const MAX_SOURCE_DEPTH: usize = 16;
+
+/// The type of a command argument; decides how it is validated and completed
+#[derive(Clone)]
+pub enum ArgKind {
+    /// any word
+    Text,
+    /// a whole number
+    Int,
+    /// a float
+    Float,
+    /// one of a fixed list (e.g. the variants of a `PromptableEnum`)
+    Choice(Vec<String>),
+    /// a file name, optionally with the given extension
+    File(Option<String>),
+    /// one of a list that is only known at runtime (asked again for every completion)
+    List(Arc<dyn Fn() -> Vec<String> + Send + Sync>),
+}
+
+/// One argument of a `CommandSpec`
+#[derive(Clone)]
+pub struct ArgSpec {
+    pub name: String,
+    pub kind: ArgKind,
+    pub optional: bool,
+}
+
+impl ArgSpec {
+    fn new(name: &str, kind: ArgKind) -> Self {
+        ArgSpec {
+            name: name.to_string(),
+            kind,
+            optional: false,
+        }
+    }
+
+    pub fn text(name: &str) -> Self {
+        Self::new(name, ArgKind::Text)
+    }
+
+    pub fn int(name: &str) -> Self {
+        Self::new(name, ArgKind::Int)
+    }
+
+    pub fn float(name: &str) -> Self {
+        Self::new(name, ArgKind::Float)
+    }
+
+    pub fn choice(name: &str, choices: &[&str]) -> Self {
+        Self::new(
+            name,
+            ArgKind::Choice(choices.iter().map(|c| c.to_string()).collect()),
+        )
+    }
+
+    /// an argument completed (and validated) from the variants of `E`
+    pub fn enumeration<E: PromptableEnum>(name: &str) -> Self {
+        Self::new(name, ArgKind::Choice(E::variants_as_strings()))
+    }
+
+    pub fn file(name: &str, extension: Option<&str>) -> Self {
+        Self::new(name, ArgKind::File(extension.map(str::to_string)))
+    }
+
+    /// an argument from a runtime list, e.g. the items currently in an inventory
+    pub fn list(name: &str, list: impl Fn() -> Vec<String> + Send + Sync + 'static) -> Self {
+        Self::new(name, ArgKind::List(Arc::new(list)))
+    }
+
+    /// makes the argument optional; optional arguments must come last
+    pub fn optional(mut self) -> Self {
+        self.optional = true;
+        self
+    }
+
+    fn usage(&self) -> String {
+        if self.optional {
+            format!("[{}]", self.name)
+        } else {
+            format!("<{}>", self.name)
+        }
+    }
+
+    fn candidates(&self) -> Vec<String> {
+        match &self.kind {
+            ArgKind::Choice(choices) => choices.clone(),
+            ArgKind::List(list) => list(),
+            _ => Vec::new(),
+        }
+    }
+
+    /* validates one word; returns the canonical value (e.g. the spelling of the choice) */
+    fn check(&self, word: &str) -> Result<String, String> {
+        match &self.kind {
+            ArgKind::Text | ArgKind::File(_) => Ok(word.to_string()),
+            ArgKind::Int => word
+                .parse::<i64>()
+                .map(|_| word.to_string())
+                .map_err(|_| format!("expected a whole number for {}", self.usage())),
+            ArgKind::Float => word
+                .parse::<f64>()
+                .map(|_| word.to_string())
+                .map_err(|_| format!("expected a number for {}", self.usage())),
+            ArgKind::Choice(_) | ArgKind::List(_) => {
+                let candidates = self.candidates();
+                candidates
+                    .iter()
+                    .find(|c| c.eq_ignore_ascii_case(word))
+                    .cloned()
+                    .ok_or_else(|| {
+                        format!("{} must be one of: {}", self.usage(), candidates.join(", "))
+                    })
+            }
+        }
+    }
+}
+
+/// A command of a `Repl`: its name, help text and arguments
+#[derive(Clone)]
+pub struct CommandSpec {
+    pub name: String,
+    pub help: String,
+    pub args: Vec<ArgSpec>,
+}
+
+impl CommandSpec {
+    pub fn new(name: &str, help: &str) -> Self {
+        CommandSpec {
+            name: name.to_string(),
+            help: help.to_string(),
+            args: Vec::new(),
+        }
+    }
+
+    /// adds the next argument
+    pub fn arg(mut self, arg: ArgSpec) -> Self {
+        self.args.push(arg);
+        self
+    }
+
+    /// returns the usage line, e.g. `buy <item> [count]`
+    pub fn usage(&self) -> String {
+        let mut parts = vec![self.name.clone()];
+        parts.extend(self.args.iter().map(|a| a.usage()));
+        parts.join(" ")
+    }
+
+    fn check_args(&self, words: &[String]) -> Result<CommandArgs, String> {
+        let required = self.args.iter().filter(|a| !a.optional).count();
+        if words.len() < required || words.len() > self.args.len() {
+            return Err(format!(
+                "{} expects {} argument(s)",
+                self.name,
+                if required == self.args.len() {
+                    required.to_string()
+                } else {
+                    format!("{} to {}", required, self.args.len())
+                }
+            ));
+        }
+        let values = self
+            .args
+            .iter()
+            .zip(words)
+            .map(|(spec, word)| spec.check(word))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(CommandArgs { values })
+    }
+}
+
+/// The validated arguments of a command, passed to `ReplCommand::from_args`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandArgs {
+    values: Vec<String>,
+}
+
+impl CommandArgs {
+    pub fn get(&self, idx: usize) -> Option<&str> {
+        self.values.get(idx).map(|v| v.as_str())
+    }
+
+    /// parses the argument at `idx`, e.g. into a number
+    pub fn parse<T: FromStr>(&self, idx: usize) -> Option<T> {
+        self.get(idx).and_then(|v| v.parse().ok())
+    }
+
+    /// returns the argument at `idx` as variant of `E`
+    pub fn get_enum<E: PromptableEnum>(&self, idx: usize) -> Option<E> {
+        self.get(idx).and_then(E::from_input_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+/// A command type for `Repl`, usually an enum whose variants carry the typed arguments:
+///
+/// ```ignore
+/// enum Cmd { Buy(Item, usize), Look }
+/// impl ReplCommand for Cmd {
+///     fn specs() -> Vec<CommandSpec> {
+///         vec![
+///             CommandSpec::new("buy", "Buy an item")
+///                 .arg(ArgSpec::enumeration::<Item>("item"))
+///                 .arg(ArgSpec::int("count").optional()),
+///             CommandSpec::new("look", "Look around"),
+///         ]
+///     }
+///     fn from_args(name: &str, args: &CommandArgs) -> Option<Self> {
+///         match name {
+///             "buy" => Some(Cmd::Buy(args.get_enum(0)?, args.parse(1).unwrap_or(1))),
+///             "look" => Some(Cmd::Look),
+///             _ => None,
+///         }
+///     }
+/// }
+/// ```
+pub trait ReplCommand: Sized {
+    /// all commands with their arguments; used for completion, validation and help
+    fn specs() -> Vec<CommandSpec>;
+
+    /// builds the command; the arguments are already checked against the spec of `name`
+    fn from_args(name: &str, args: &CommandArgs) -> Option<Self>;
+}
+
+/// What the `Repl` does after a command was handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplFlow {
+    Continue,
+    Exit,
+}
+
+/// A command loop on top of `AdvInput`. Lines are split shell style (`buy "long sword" 3`),
+/// Tab completes the command name and then each argument according to its `ArgKind`, and
+/// errors are reported with the usage of the command.
+///
+/// Built in are `help`, `exit` / `quit` and some scripting support: `source <file>` runs the
+/// lines of a file, `alias name=command` defines shortcuts that are expanded before parsing,
+/// `set name=value` defines variables substituted as `$name` (or `${name}`), and blank lines and
+/// lines starting with `#` are ignored. Errors in scripts report the file and line number.
+pub struct Repl<C: ReplCommand> {
+    prompt: ColoredString,
+    specs: Vec<CommandSpec>,
+    aliases: BTreeMap<String, String>,
+    variables: BTreeMap<String, String>,
+    _command: PhantomData<C>,
+}
+
+impl<C: ReplCommand> Repl<C> {
+    pub fn new(prompt: impl Into<ColoredString>) -> Self {
+        Repl {
+            prompt: prompt.into(),
+            specs: C::specs(),
+            aliases: BTreeMap::new(),
+            variables: BTreeMap::new(),
+            _command: PhantomData,
+        }
+    }
+
+    /// reads and executes commands until `exit`, Ctrl-D or a handler returning `ReplFlow::Exit`.
+    /// Ctrl-C only discards the current line
+    pub fn run<F>(&mut self, adv: &mut AdvInput, mut handler: F)
+    where
+        F: FnMut(&mut AdvInput, C) -> ReplFlow,
+    {
+        let prompt = adv.prompt_text(self.prompt.clone());
+        let files = Arc::new(FileCompleters::default());
+        loop {
+            // handlers may use other prompts, so the helper is set for every line (sharing the
+            // file completers, whose directory scans are cached)
+            adv.set_active_helper(ActiveHelper::Command(
+                CommandCompleterHelper::new(
+                    self.all_specs(),
+                    self.aliases.keys().cloned().collect(),
+                )
+                .with_files(Arc::clone(&files)),
+            ));
+            match adv.readline_raw(&prompt, "") {
+                Ok(line) => match self.execute_line(adv, &line, &mut handler) {
+                    Ok(ReplFlow::Continue) => {}
+                    Ok(ReplFlow::Exit) => break,
+                    Err(msg) => eprintln!("{}", adv.err_theme().error.paint(&msg)),
+                },
+                Err(ReadlineError::Interrupted) => continue,
+                Err(_) => break,
+            }
+        }
+    }
+
+    /// executes one line; returns the error message (including the usage) if it is not valid
+    pub fn execute_line<F>(
+        &mut self,
+        adv: &mut AdvInput,
+        line: &str,
+        handler: &mut F,
+    ) -> Result<ReplFlow, String>
+    where
+        F: FnMut(&mut AdvInput, C) -> ReplFlow,
+    {
+        self.execute(adv, line, handler, 0)
+    }
+
+    /// executes all lines of a script file, stopping at the first error, which is reported as
+    /// `file:line: message`
+    pub fn source_file<F>(
+        &mut self,
+        adv: &mut AdvInput,
+        path: &Path,
+        handler: &mut F,
+    ) -> Result<ReplFlow, String>
+    where
+        F: FnMut(&mut AdvInput, C) -> ReplFlow,
+    {
+        self.source(adv, path, handler, 0)
+    }
+
+    /// defines an alias; `expansion` replaces the first word of a line named `name`
+    pub fn set_alias(&mut self, name: &str, expansion: &str) {
+        self.aliases.insert(name.to_string(), expansion.to_string());
+    }
+
+    /// defines a variable, substituted for `$name` in every following line
+    pub fn set_variable(&mut self, name: &str, value: &str) {
+        self.variables.insert(name.to_string(), value.to_string());
+    }
+
+    fn execute<F>(
+        &mut self,
+        adv: &mut AdvInput,
+        line: &str,
+        handler: &mut F,
+        depth: usize,
+    ) -> Result<ReplFlow, String>
+    where
+        F: FnMut(&mut AdvInput, C) -> ReplFlow,
+    {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(COMMENT) {
+            return Ok(ReplFlow::Continue);
+        }
+        let line = self.expand_alias(line)?;
+        // an alias keeps its text as typed, so quotes and `$name` are resolved when it is used
+        if let Some((first, rest)) = line.split_once(char::is_whitespace)
+            && first.eq_ignore_ascii_case(ALIAS_COMMAND)
+        {
+            let (key, value) = parse_definition(rest.trim())
+                .ok_or_else(|| format!("usage: {} name=command", ALIAS_COMMAND))?;
+            self.aliases
+                .insert(key.to_string(), unquote(value).to_string());
+            return Ok(ReplFlow::Continue);
+        }
+        let line = self.substitute_variables(&line)?;
+        let words = split_words(&line)?;
+        let Some((name, args)) = words.split_first() else {
+            return Ok(ReplFlow::Continue);
+        };
+        let name = name.to_lowercase();
+        match name.as_str() {
+            HELP_COMMAND => {
+                self.print_help(adv.out_theme(), args.first().map(|a| a.as_str()));
+                return Ok(ReplFlow::Continue);
+            }
+            SOURCE_COMMAND => {
+                let [file] = args else {
+                    return Err(format!("usage: {} <file>", SOURCE_COMMAND));
+                };
+                return self.source(adv, Path::new(file), handler, depth + 1);
+            }
+            ALIAS_COMMAND | SET_COMMAND => {
+                let table = if name == ALIAS_COMMAND {
+                    &mut self.aliases
+                } else {
+                    &mut self.variables
+                };
+                match args {
+                    [] => print_table(adv.out_theme(), table),
+                    [definition] => {
+                        let (key, value) = parse_definition(definition)
+                            .ok_or_else(|| format!("usage: {} name=value", name))?;
+                        table.insert(key.to_string(), value.to_string());
+                    }
+                    _ => {
+                        return Err(format!(
+                            "usage: {} name=value (quote values with spaces)",
+                            name
+                        ));
+                    }
+                }
+                return Ok(ReplFlow::Continue);
+            }
+            UNALIAS_COMMAND | UNSET_COMMAND => {
+                let table = if name == UNALIAS_COMMAND {
+                    &mut self.aliases
+                } else {
+                    &mut self.variables
+                };
+                let [key] = args else {
+                    return Err(format!("usage: {} <name>", name));
+                };
+                return match table.remove(key) {
+                    Some(_) => Ok(ReplFlow::Continue),
+                    None => Err(format!("'{}' is not defined", key)),
+                };
+            }
+            _ => {}
+        }
+        if EXIT_COMMANDS.contains(&name.as_str()) {
+            return Ok(ReplFlow::Exit);
+        }
+        let spec = self
+            .spec(&name)
+            .ok_or_else(|| format!("unknown command '{}', try '{}'", name, HELP_COMMAND))?;
+        let usage = || format!("usage: {}", spec.usage());
+        let checked = spec
+            .check_args(args)
+            .map_err(|e| format!("{}\n{}", e, usage()))?;
+        let command = C::from_args(&spec.name, &checked).ok_or_else(usage)?;
+        Ok(handler(adv, command))
+    }
+
+    fn source<F>(
+        &mut self,
+        adv: &mut AdvInput,
+        path: &Path,
+        handler: &mut F,
+        depth: usize,
+    ) -> Result<ReplFlow, String>
+    where
+        F: FnMut(&mut AdvInput, C) -> ReplFlow,
+    {
+        if depth > MAX_SOURCE_DEPTH {
+            return Err(format!("scripts nested deeper than {}", MAX_SOURCE_DEPTH));
+        }
+        let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        for (idx, line) in content.lines().enumerate() {
+            match self.execute(adv, line, handler, depth) {
+                Ok(ReplFlow::Continue) => {}
+                Ok(ReplFlow::Exit) => return Ok(ReplFlow::Exit),
+                Err(msg) => return Err(format!("{}:{}: {}", path.display(), idx + 1, msg)),
+            }
+        }
+        Ok(ReplFlow::Continue)
+    }
+
+    /* replaces the first word by its alias (repeatedly, but every alias only once) */
+    fn expand_alias(&self, line: &str) -> Result<String, String> {
+        let mut line = line.to_string();
+        let mut expanded: Vec<String> = Vec::new();
+        loop {
+            let (first, rest) = line
+                .split_once(char::is_whitespace)
+                .unwrap_or((line.as_str(), ""));
+            let Some(expansion) = self.aliases.get(first) else {
+                return Ok(line);
+            };
+            if expanded.iter().any(|e| e == first) {
+                return Err(format!("alias '{}' expands to itself", first));
+            }
+            expanded.push(first.to_string());
+            line = format!("{} {}", expansion, rest).trim_end().to_string();
+        }
+    }
+
+    /* replaces `$name` and `${name}` outside of single quotes; `\$` keeps the dollar sign */
+    fn substitute_variables(&self, line: &str) -> Result<String, String> {
+        let mut result = String::with_capacity(line.len());
+        let mut in_single_quotes = false;
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' if !in_single_quotes => {
+                    result.push(c);
+                    if let Some(next) = chars.next() {
+                        result.push(next);
+                    }
+                }
+                '\'' => {
+                    in_single_quotes = !in_single_quotes;
+                    result.push(c);
+                }
+                '$' if !in_single_quotes => {
+                    let braced = chars.next_if_eq(&'{').is_some();
+                    let mut name = String::new();
+                    while let Some(n) = chars.next_if(|n| n.is_alphanumeric() || *n == '_') {
+                        name.push(n);
+                    }
+                    if braced && chars.next_if_eq(&'}').is_none() {
+                        return Err("missing closing } after ${".to_string());
+                    }
+                    if name.is_empty() {
+                        result.push('$');
+                        if braced {
+                            result.push_str("{}");
+                        }
+                        continue;
+                    }
+                    let value = self
+                        .variables
+                        .get(&name)
+                        .ok_or_else(|| format!("unknown variable ${}", name))?;
+                    result.push_str(value);
+                }
+                _ => result.push(c),
+            }
+        }
+        Ok(result)
+    }
+
+    /// prints all commands with their usage, or the help of one command
+    pub fn print_help(&self, theme: &Theme, command: Option<&str>) {
+        let builtins = builtin_specs();
+        let specs: Vec<&CommandSpec> = match command.and_then(|c| self.spec(c)) {
+            Some(spec) => vec![spec],
+            None => match command.and_then(|c| builtins.iter().find(|s| s.name == c)) {
+                Some(spec) => vec![spec],
+                None => self.specs.iter().chain(&builtins).collect(),
+            },
+        };
+        let width = specs.iter().map(|s| s.usage().len()).max().unwrap_or(0);
+        for spec in specs {
+            println!(
+                "  {:width$}  {}",
+                theme.heading.paint(&spec.usage()),
+                theme.hint.paint(&spec.help),
+                width = width
+            );
+        }
+    }
+
+    fn spec(&self, name: &str) -> Option<&CommandSpec> {
+        self.specs
+            .iter()
+            .find(|s| s.name.eq_ignore_ascii_case(name))
+    }
+
+    fn all_specs(&self) -> Vec<CommandSpec> {
+        self.specs.iter().cloned().chain(builtin_specs()).collect()
+    }
+}
+
+/* the commands handled by the Repl itself, for help and completion */
+fn builtin_specs() -> Vec<CommandSpec> {
+    vec![
+        CommandSpec::new(HELP_COMMAND, "Show all commands, or the usage of one")
+            .arg(ArgSpec::text("command").optional()),
+        CommandSpec::new(SOURCE_COMMAND, "Run the commands of a script file")
+            .arg(ArgSpec::file("file", None)),
+        CommandSpec::new(ALIAS_COMMAND, "Define a shortcut, or list all")
+            .arg(ArgSpec::text("name=command").optional()),
+        CommandSpec::new(UNALIAS_COMMAND, "Remove a shortcut").arg(ArgSpec::text("name")),
+        CommandSpec::new(SET_COMMAND, "Define a variable used as $name, or list all")
+            .arg(ArgSpec::text("name=value").optional()),
+        CommandSpec::new(UNSET_COMMAND, "Remove a variable").arg(ArgSpec::text("name")),
+        CommandSpec::new(EXIT_COMMANDS[0], "Leave (also: quit)"),
+    ]
+}
+
+/* removes one pair of quotes surrounding the whole value */
+fn unquote(value: &str) -> &str {
+    for q in ['"', '\''] {
+        if let Some(inner) = value.strip_prefix(q).and_then(|v| v.strip_suffix(q)) {
+            return inner;
+        }
+    }
+    value
+}
+
+/* splits `name=value`; the name must be a single word */
+fn parse_definition(definition: &str) -> Option<(&str, &str)> {
+    definition
+        .split_once('=')
+        .filter(|(name, _)| !name.is_empty() && !name.contains(char::is_whitespace))
+}
+
+fn print_table(theme: &Theme, table: &BTreeMap<String, String>) {
+    for (key, value) in table {
+        println!("  {}={}", theme.heading.paint(key), value);
+    }
+}
+
+/* a word of a command line and the byte offset where it starts */
+struct Word {
+    start: usize,
+    text: String,
+}
+
+/* splits shell style; returns the words and the open quote, if the line ends inside one */
+fn scan_words(line: &str) -> (Vec<Word>, Option<char>) {
+    let mut words = Vec::new();
+    let mut current: Option<Word> = None;
+    let mut quote: Option<char> = None;
+    let mut chars = line.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"'), '\\') | (None, '\\') => {
+                if let Some((_, escaped)) = chars.next() {
+                    current
+                        .get_or_insert(Word {
+                            start: i,
+                            text: String::new(),
+                        })
+                        .text
+                        .push(escaped);
+                }
+            }
+            (Some(_), c) => current.as_mut().expect("quote opens a word").text.push(c),
+            (None, '"') | (None, '\'') => {
+                quote = Some(c);
+                current.get_or_insert(Word {
+                    start: i,
+                    text: String::new(),
+                });
+            }
+            (None, c) if c.is_whitespace() => {
+                if let Some(word) = current.take() {
+                    words.push(word);
+                }
+            }
+            (None, c) => current
+                .get_or_insert(Word {
+                    start: i,
+                    text: String::new(),
+                })
+                .text
+                .push(c),
+        }
+    }
+    if let Some(word) = current {
+        words.push(word);
+    }
+    (words, quote)
+}
+
+/// splits a command line into words like a shell does: whitespace separates words, single and
+/// double quotes group them, and a backslash escapes the next character
+pub fn split_words(line: &str) -> Result<Vec<String>, String> {
+    match scan_words(line) {
+        (_, Some(q)) => Err(format!("missing closing {}", q)),
+        (words, None) => Ok(words.into_iter().map(|w| w.text).collect()),
+    }
+}
+
+/* quotes a completion candidate if it would otherwise be split */
+fn quote_word(word: &str) -> String {
+    if word.is_empty() || word.contains(|c: char| c.is_whitespace() || c == '"' || c == '\'') {
+        format!("\"{}\"", word.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        word.to_string()
+    }
+}
+
+/* the completers of file arguments, one per extension */
+#[derive(Default)]
+struct FileCompleters(Mutex<HashMap<Option<String>, Arc<FileCompleterHelper>>>);
+
+impl FileCompleters {
+    fn get(&self, ext: &Option<String>) -> Arc<FileCompleterHelper> {
+        let mut completers = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let completer = completers.entry(ext.clone()).or_insert_with(|| {
+            let filter = match ext {
+                Some(ext) => FileFilter::extensions(&[ext.as_str()]),
+                None => FileFilter::All,
+            };
+            Arc::new(FileCompleterHelper::new(
+                PathBuf::from("."),
+                filter,
+                FileInputOptions::default(),
+            ))
+        });
+        Arc::clone(completer)
+    }
+}
+
+/// a rustyline helper completing command names and then each argument by its `ArgKind`
+pub struct CommandCompleterHelper {
+    specs: Vec<CommandSpec>,
+    aliases: Vec<String>,
+    files: Arc<FileCompleters>,
+}
+
+impl CommandCompleterHelper {
+    pub fn new(specs: Vec<CommandSpec>, aliases: Vec<String>) -> Self {
+        CommandCompleterHelper {
+            specs,
+            aliases,
+            files: Arc::default(),
+        }
+    }
+
+    /* shares the file completers (and their cached scans) with the helpers of other lines */
+    fn with_files(mut self, files: Arc<FileCompleters>) -> Self {
+        self.files = files;
+        self
+    }
+
+    fn command_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.specs.iter().map(|s| s.name.clone()).collect();
+        names.push(EXIT_COMMANDS[1].to_string());
+        names.extend(self.aliases.iter().cloned());
+        names
+    }
+}
+
+impl Completer for CommandCompleterHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &rustyline::Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let (mut words, quote) = scan_words(&line[..pos]);
+        /* the word under the cursor (empty after whitespace) and the position of that word */
+        let ends_in_space = quote.is_none() && line[..pos].ends_with(char::is_whitespace);
+        let current = if words.is_empty() || ends_in_space {
+            Word {
+                start: pos,
+                text: String::new(),
+            }
+        } else {
+            words.pop().expect("checked above")
+        };
+        let candidates = match words.first() {
+            None => self.command_names(),
+            Some(cmd) if cmd.text.eq_ignore_ascii_case(HELP_COMMAND) && words.len() == 1 => {
+                self.specs.iter().map(|s| s.name.clone()).collect()
+            }
+            Some(cmd) => {
+                let arg = self
+                    .specs
+                    .iter()
+                    .find(|s| s.name.eq_ignore_ascii_case(&cmd.text))
+                    .and_then(|s| s.args.get(words.len() - 1));
+                match arg {
+                    Some(ArgSpec {
+                        kind: ArgKind::File(ext),
+                        ..
+                    }) => {
+                        /* the word is unquoted already (e.g. after `"my fi`), and is replaced
+                        from its start, quoted again if needed */
+                        let matches = self
+                            .files
+                            .get(ext)
+                            .complete_word(&current.text)
+                            .into_iter()
+                            .map(|pair| Pair {
+                                replacement: quote_word(&pair.replacement),
+                                ..pair
+                            })
+                            .collect();
+                        return Ok((current.start, matches));
+                    }
+                    Some(arg) => arg.candidates(),
+                    None => Vec::new(),
+                }
+            }
+        };
+        let prefix = current.text.to_lowercase();
+        let matches = candidates
+            .into_iter()
+            .filter(|c| c.to_lowercase().starts_with(&prefix))
+            .map(|c| Pair {
+                replacement: quote_word(&c),
+                display: c,
+            })
+            .collect();
+        Ok((current.start, matches))
+    }
+}
+
+impl Highlighter for CommandCompleterHelper {
+    fn highlight<'l>(&self, line: &'l str, _pos: usize) -> Cow<'l, str> {
+        Cow::Borrowed(line)
+    }
+}
+
+impl Hinter for CommandCompleterHelper {
+    type Hint = String;
+    fn hint(&self, _line: &str, _pos: usize, _ctx: &rustyline::Context<'_>) -> Option<Self::Hint> {
+        None
+    }
+}
+
+impl Validator for CommandCompleterHelper {}
+impl rustyline::Helper for CommandCompleterHelper {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    enum TestCommand {
+        Say(String),
+    }
+
+    impl ReplCommand for TestCommand {
+        fn specs() -> Vec<CommandSpec> {
+            vec![CommandSpec::new("say", "Print a word").arg(ArgSpec::text("word"))]
+        }
+
+        fn from_args(name: &str, args: &CommandArgs) -> Option<Self> {
+            match name {
+                "say" => Some(TestCommand::Say(args.get(0)?.to_string())),
+                _ => None,
+            }
+        }
+    }
+
+    /* runs the lines and returns the words said, or the first error */
+    fn run(repl: &mut Repl<TestCommand>, lines: &[&str]) -> Result<Vec<String>, String> {
+        let mut adv = AdvInput::new();
+        let mut said = Vec::new();
+        let mut handler = |_: &mut AdvInput, TestCommand::Say(word)| {
+            said.push(word);
+            ReplFlow::Continue
+        };
+        for line in lines {
+            repl.execute_line(&mut adv, line, &mut handler)?;
+        }
+        Ok(said)
+    }
+
+    #[test]
+    fn split_words_groups_quotes_and_escapes() {
+        assert_eq!(
+            split_words(r#"buy "long sword" 3"#).unwrap(),
+            ["buy", "long sword", "3"]
+        );
+        assert_eq!(
+            split_words(r#"'a "b"' c\ d "e \" f""#).unwrap(),
+            [r#"a "b""#, "c d", r#"e " f"#]
+        );
+        assert_eq!(split_words(r#"x"y"z ''"#).unwrap(), ["xyz", ""]);
+        assert_eq!(split_words("say 'open").unwrap_err(), "missing closing '");
+        assert_eq!(
+            split_words(r#"say "open"#).unwrap_err(),
+            "missing closing \""
+        );
+    }
+
+    #[test]
+    fn scan_words_reports_offsets_and_open_quote() {
+        let (words, quote) = scan_words(r#"go  "far away"#);
+        let starts: Vec<usize> = words.iter().map(|w| w.start).collect();
+        assert_eq!(starts, [0, 4]);
+        assert_eq!(words[1].text, "far away");
+        assert_eq!(quote, Some('"'));
+    }
+
+    #[test]
+    fn variables_are_substituted() {
+        let mut repl = Repl::<TestCommand>::new("> ");
+        repl.set_variable("x", "5");
+        assert_eq!(repl.substitute_variables("say $x").unwrap(), "say 5");
+        assert_eq!(repl.substitute_variables("say ${x}th").unwrap(), "say 5th");
+        assert_eq!(repl.substitute_variables("say '$x'").unwrap(), "say '$x'");
+        assert_eq!(repl.substitute_variables(r"say \$x").unwrap(), r"say \$x");
+        assert_eq!(repl.substitute_variables("say $ ${}").unwrap(), "say $ ${}");
+        assert_eq!(
+            run(&mut repl, &[r"say \$x", "say '$x'", "say $x"]).unwrap(),
+            ["$x", "$x", "5"]
+        );
+    }
+
+    #[test]
+    fn unknown_or_unclosed_variables_are_errors() {
+        let repl = Repl::<TestCommand>::new("> ");
+        assert_eq!(
+            repl.substitute_variables("say $y").unwrap_err(),
+            "unknown variable $y"
+        );
+        assert_eq!(
+            repl.substitute_variables("say ${y").unwrap_err(),
+            "missing closing } after ${"
+        );
+    }
+
+    #[test]
+    fn set_and_unset_define_variables() {
+        let mut repl = Repl::<TestCommand>::new("> ");
+        assert_eq!(
+            run(&mut repl, &["set word='two words'", "say \"$word\""]).unwrap(),
+            ["two words"]
+        );
+        // substituted before splitting, like in a shell
+        assert_eq!(
+            run(&mut repl, &["say $word"]).unwrap_err(),
+            "say expects 1 argument(s)\nusage: say <word>"
+        );
+        assert_eq!(
+            run(&mut repl, &["unset word", "say $word"]).unwrap_err(),
+            "unknown variable $word"
+        );
+        assert_eq!(
+            run(&mut repl, &["unset word"]).unwrap_err(),
+            "'word' is not defined"
+        );
+    }
+
+    #[test]
+    fn aliases_expand_the_first_word() {
+        let mut repl = Repl::<TestCommand>::new("> ");
+        repl.set_alias("hi", "say hello");
+        repl.set_alias("s", "say");
+        repl.set_alias("again", "s");
+        assert_eq!(repl.expand_alias("again there").unwrap(), "say there");
+        assert_eq!(repl.expand_alias("hi").unwrap(), "say hello");
+        assert_eq!(repl.expand_alias("say hi").unwrap(), "say hi");
+        assert_eq!(
+            run(&mut repl, &["hi", "alias yo='say \"yo yo\"'", "yo"]).unwrap(),
+            ["hello", "yo yo"]
+        );
+    }
+
+    #[test]
+    fn recursive_aliases_are_errors() {
+        let mut repl = Repl::<TestCommand>::new("> ");
+        repl.set_alias("a", "b x");
+        repl.set_alias("b", "a");
+        assert_eq!(
+            repl.expand_alias("a").unwrap_err(),
+            "alias 'a' expands to itself"
+        );
+        repl.set_alias("c", "c");
+        assert_eq!(
+            run(&mut repl, &["c"]).unwrap_err(),
+            "alias 'c' expands to itself"
+        );
+    }
+
+    #[test]
+    fn definitions_need_a_single_word_name() {
+        assert_eq!(parse_definition("x=1"), Some(("x", "1")));
+        assert_eq!(parse_definition("x=a=b"), Some(("x", "a=b")));
+        assert_eq!(parse_definition("x="), Some(("x", "")));
+        assert_eq!(parse_definition("=1"), None);
+        assert_eq!(parse_definition("a b=1"), None);
+        assert_eq!(parse_definition("x"), None);
+    }
+
+    #[test]
+    fn nested_source_errors_name_every_file_and_line() {
+        let dir = std::env::temp_dir().join(format!("advanced_inputs_repl_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let outer = dir.join("outer.txt");
+        let inner = dir.join("inner.txt");
+        fs::write(&inner, "# comment\nsay two\n\nbogus\n").unwrap();
+        fs::write(&outer, format!("say one\nsource \"{}\"\n", inner.display())).unwrap();
+
+        let mut repl = Repl::<TestCommand>::new("> ");
+        let err = run(&mut repl, &[&format!("source \"{}\"", outer.display())]).unwrap_err();
+        assert_eq!(
+            err,
+            format!(
+                "{}:2: {}:4: unknown command 'bogus', try 'help'",
+                outer.display(),
+                inner.display()
+            )
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn file_arguments_complete_inside_quotes() {
+        let dir =
+            std::env::temp_dir().join(format!("advanced_inputs_quote_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("my file.txt"), "").unwrap();
+        fs::write(dir.join("other.txt"), "").unwrap();
+
+        let helper =
+            CommandCompleterHelper::new(Repl::<TestCommand>::new("> ").all_specs(), vec![]);
+        let history = rustyline::history::DefaultHistory::new();
+        let ctx = rustyline::Context::new(&history);
+        for line in [
+            format!("source \"{}/my fi", dir.display()),
+            format!("source {}/my\\ fi", dir.display()),
+        ] {
+            let (start, matches) = helper.complete(&line, line.len(), &ctx).unwrap();
+            assert_eq!(start, "source ".len());
+            let replacements: Vec<&str> = matches.iter().map(|m| m.replacement.as_str()).collect();
+            assert_eq!(replacements, [format!("\"{}/my file.txt\"", dir.display())]);
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,67 @@
+use crate::store::{data_file, load_records, save_records};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::PathBuf;
+
+/// The last accepted answer of every sticky prompt (see `AdvInput::sticky`), used as the
+/// initial value the next time the prompt is shown.
+/// With a file set, the answers are loaded from it and saved after every change
+#[derive(Debug, Clone, Default)]
+pub struct StickyAnswers {
+    answers: BTreeMap<String, String>,
+    file: Option<PathBuf>,
+}
+
+impl StickyAnswers {
+    /// no answers, kept in memory only
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// keeps the answers in `file`, like `PromptHistory::set_file` does
+    pub fn set_file(&mut self, file: PathBuf) -> io::Result<()> {
+        for record in load_records(&file)? {
+            if let [id, answer] = &record[..] {
+                self.answers.insert(id.clone(), answer.clone());
+            }
+        }
+        self.file = Some(file);
+        Ok(())
+    }
+
+    /// `<name>.answers` next to `PromptHistory::default_file`
+    pub fn default_file(name: &str) -> Option<PathBuf> {
+        data_file(name, "answers")
+    }
+
+    /// the last answer to prompt `id`
+    pub fn get(&self, id: &str) -> Option<&str> {
+        self.answers.get(id).map(String::as_str)
+    }
+
+    /// stores the answer to prompt `id` and saves the answers if they have a file
+    pub fn set(&mut self, id: &str, answer: &str) -> io::Result<()> {
+        if self.get(id) == Some(answer) {
+            return Ok(());
+        }
+        self.answers.insert(id.to_string(), answer.to_string());
+        self.save()
+    }
+
+    /// forgets the answer to prompt `id`
+    pub fn remove(&mut self, id: &str) -> io::Result<()> {
+        if self.answers.remove(id).is_some() {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// writes the answers to their file (if they have one)
+    pub fn save(&self) -> io::Result<()> {
+        let records = self
+            .answers
+            .iter()
+            .map(|(id, answer)| vec![id.clone(), answer.clone()]);
+        save_records(self.file.as_deref(), records)
+    }
+}
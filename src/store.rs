@@ -0,0 +1,100 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/* the state files of `PromptHistory`, `StickyAnswers` and `EnumUsage`: one record per line,
+with tab separated (and escaped) fields */
+
+/* `<name>.<extension>` in the user's data directory (e.g. `~/.local/share/advanced_inputs/`) */
+pub(crate) fn data_file(name: &str, extension: &str) -> Option<PathBuf> {
+    let dir = dirs::data_dir()?.join("advanced_inputs");
+    Some(dir.join(format!("{}.{}", name, extension)))
+}
+
+/* the records of `file`; a missing file has none */
+pub(crate) fn load_records(file: &Path) -> io::Result<Vec<Vec<String>>> {
+    match fs::read_to_string(file) {
+        Ok(content) => Ok(content
+            .lines()
+            .map(|line| line.split('\t').map(unescape).collect())
+            .collect()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/* writes the records to `file` (if there is one), creating its directory */
+pub(crate) fn save_records<I>(file: Option<&Path>, records: I) -> io::Result<()>
+where
+    I: IntoIterator<Item = Vec<String>>,
+{
+    let Some(file) = file else {
+        return Ok(());
+    };
+    if let Some(dir) = file.parent()
+        && !dir.as_os_str().is_empty()
+    {
+        fs::create_dir_all(dir)?;
+    }
+    let mut content = String::new();
+    for record in records {
+        let fields: Vec<String> = record.iter().map(|field| escape(field)).collect();
+        content.push_str(&fields.join("\t"));
+        content.push('\n');
+    }
+    fs::write(file, content)
+}
+
+/* tabs, newlines and backslashes would break the line format */
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+}
+
+fn unescape(s: &str) -> String {
+    let mut unescaped = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('t') => unescaped.push('\t'),
+                Some('n') => unescaped.push('\n'),
+                other => unescaped.extend(other),
+            },
+            c => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_round_trip_with_special_characters() {
+        let dir =
+            std::env::temp_dir().join(format!("advanced_inputs_store_{}", std::process::id()));
+        let file = dir.join("nested").join("records.txt");
+        let records = vec![
+            vec!["name".to_string(), "a\tb".to_string()],
+            vec!["two\nlines".to_string(), String::new()],
+            vec![r"C:\temp\new".to_string(), r"\t is not a tab".to_string()],
+        ];
+        save_records(Some(&file), records.clone()).unwrap();
+        let content = fs::read_to_string(&file).unwrap();
+        assert_eq!(content.lines().count(), records.len());
+        assert_eq!(load_records(&file).unwrap(), records);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn a_missing_file_has_no_records() {
+        let file = std::env::temp_dir()
+            .join("advanced_inputs_store_missing")
+            .join("none.txt");
+        assert!(load_records(&file).unwrap().is_empty());
+        assert!(save_records(None, vec![vec!["x".to_string()]]).is_ok());
+    }
+}
@@ -0,0 +1,384 @@
+use colored::Color;
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
+
+/// How one kind of output looks: an optional color plus text attributes.
+/// Parses from strings like `"bright magenta italic"`, `"#ff8800 bold"` or `"none"`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Style {
+    pub color: Option<Color>,
+    pub bold: bool,
+    pub italic: bool,
+    pub dimmed: bool,
+    pub underline: bool,
+}
+
+impl Style {
+    /// no color, no attributes
+    pub const fn plain() -> Self {
+        Style {
+            color: None,
+            bold: false,
+            italic: false,
+            dimmed: false,
+            underline: false,
+        }
+    }
+
+    pub const fn fg(color: Color) -> Self {
+        Style {
+            color: Some(color),
+            ..Self::plain()
+        }
+    }
+
+    pub const fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    pub const fn italic(mut self) -> Self {
+        self.italic = true;
+        self
+    }
+
+    pub const fn dimmed(mut self) -> Self {
+        self.dimmed = true;
+        self
+    }
+
+    pub const fn underline(mut self) -> Self {
+        self.underline = true;
+        self
+    }
+
+    pub fn is_plain(&self) -> bool {
+        *self == Self::plain()
+    }
+
+    /// applies the style to `text`. The escape codes are written regardless of `colored`'s
+    /// global settings, as `AdvInput` decides for each stream whether output is styled
+    pub fn paint(&self, text: &str) -> String {
+        let mut codes: Vec<Cow<'static, str>> = Vec::new();
+        if self.bold {
+            codes.push("1".into());
+        }
+        if self.dimmed {
+            codes.push("2".into());
+        }
+        if self.italic {
+            codes.push("3".into());
+        }
+        if self.underline {
+            codes.push("4".into());
+        }
+        if let Some(color) = self.color {
+            codes.push(color.to_fg_str());
+        }
+        if codes.is_empty() || text.is_empty() {
+            return text.to_string();
+        }
+        format!("\x1b[{}m{}\x1b[0m", codes.join(";"), text)
+    }
+}
+
+impl FromStr for Style {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let mut style = Style::plain();
+        let mut color_words: Vec<&str> = Vec::new();
+        for word in spec.split_whitespace() {
+            match word.to_lowercase().as_str() {
+                "none" | "plain" => {}
+                "bold" => style.bold = true,
+                "italic" => style.italic = true,
+                "dimmed" | "dim" => style.dimmed = true,
+                "underline" => style.underline = true,
+                _ => color_words.push(word),
+            }
+        }
+        if !color_words.is_empty() {
+            let name = color_words.join(" ").replace('_', " ");
+            style.color = Some(parse_color(&name).ok_or(format!("unknown color '{}'", name))?);
+        }
+        Ok(style)
+    }
+}
+
+/* a color name known to `colored`, or `#rrggbb` */
+fn parse_color(name: &str) -> Option<Color> {
+    if let Some(hex) = name.strip_prefix('#') {
+        if hex.len() != 6 {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+        return Some(Color::TrueColor {
+            r: channel(0)?,
+            g: channel(2)?,
+            b: channel(4)?,
+        });
+    }
+    name.parse().ok()
+}
+
+/// The styles of all output the library generates. Use one of the built-in themes, change
+/// single styles, or (with the `toml` feature) load overrides from a file:
+///
+/// ```toml
+/// base = "high-contrast"   # optional: default, monochrome or high-contrast
+/// warning = "bright yellow bold"
+/// variant_list = "#88aaff italic"
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// prompts that do not bring their own colors
+    pub prompt: Style,
+    /// marks the default in variant lists
+    pub default_marker: Style,
+    /// the list of enum variants or choices
+    pub variant_list: Style,
+    /// help texts, details and completion hints
+    pub hint: Style,
+    pub error: Style,
+    pub warning: Style,
+    /// confirmations, e.g. of a created directory or backup
+    pub success: Style,
+    /// file names and answers shown in lists
+    pub highlight: Style,
+    /// titles and labels
+    pub heading: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::default_theme()
+    }
+}
+
+impl Theme {
+    /// the colors the library always used
+    pub const fn default_theme() -> Self {
+        Theme {
+            prompt: Style::plain(),
+            default_marker: Style::fg(Color::BrightMagenta).italic(),
+            variant_list: Style::fg(Color::BrightMagenta).italic(),
+            hint: Style::plain().dimmed(),
+            error: Style::fg(Color::Red),
+            warning: Style::fg(Color::Yellow),
+            success: Style::fg(Color::Green),
+            highlight: Style::fg(Color::Cyan),
+            heading: Style::plain().bold(),
+        }
+    }
+
+    /// no styles at all, used while colors are off (see `AdvInput::set_color`)
+    pub const fn plain() -> Self {
+        Theme {
+            prompt: Style::plain(),
+            default_marker: Style::plain(),
+            variant_list: Style::plain(),
+            hint: Style::plain(),
+            error: Style::plain(),
+            warning: Style::plain(),
+            success: Style::plain(),
+            highlight: Style::plain(),
+            heading: Style::plain(),
+        }
+    }
+
+    /// no colors, only text attributes
+    pub const fn monochrome() -> Self {
+        Theme {
+            prompt: Style::plain(),
+            default_marker: Style::plain().bold(),
+            variant_list: Style::plain().italic(),
+            hint: Style::plain().dimmed(),
+            error: Style::plain().bold(),
+            warning: Style::plain().bold(),
+            success: Style::plain(),
+            highlight: Style::plain().underline(),
+            heading: Style::plain().bold(),
+        }
+    }
+
+    /// bright, bold colors and no dimmed text
+    pub const fn high_contrast() -> Self {
+        Theme {
+            prompt: Style::fg(Color::BrightWhite).bold(),
+            default_marker: Style::fg(Color::BrightYellow).bold(),
+            variant_list: Style::fg(Color::BrightWhite),
+            hint: Style::fg(Color::White),
+            error: Style::fg(Color::BrightRed).bold(),
+            warning: Style::fg(Color::BrightYellow).bold(),
+            success: Style::fg(Color::BrightGreen).bold(),
+            highlight: Style::fg(Color::BrightCyan).bold(),
+            heading: Style::fg(Color::BrightWhite).bold().underline(),
+        }
+    }
+
+    /// a built-in theme by name: `default`, `monochrome` or `high-contrast`
+    pub fn named(name: &str) -> Option<Self> {
+        match name.to_lowercase().replace('_', "-").as_str() {
+            "default" => Some(Self::default_theme()),
+            "monochrome" => Some(Self::monochrome()),
+            "high-contrast" => Some(Self::high_contrast()),
+            _ => None,
+        }
+    }
+
+    /// the style of a role by its field name, e.g. `"warning"`
+    pub fn style_mut(&mut self, role: &str) -> Option<&mut Style> {
+        match role {
+            "prompt" => Some(&mut self.prompt),
+            "default_marker" => Some(&mut self.default_marker),
+            "variant_list" => Some(&mut self.variant_list),
+            "hint" => Some(&mut self.hint),
+            "error" => Some(&mut self.error),
+            "warning" => Some(&mut self.warning),
+            "success" => Some(&mut self.success),
+            "highlight" => Some(&mut self.highlight),
+            "heading" => Some(&mut self.heading),
+            _ => None,
+        }
+    }
+
+    /// parses overrides (`role = "style"`, plus an optional `base` theme) from TOML text
+    #[cfg(feature = "toml")]
+    pub fn from_toml_str(s: &str) -> Result<Self, ThemeError> {
+        let table: toml::Table = toml::from_str(s).map_err(|e| ThemeError::Toml(e.to_string()))?;
+        let mut theme = match table.get("base") {
+            Some(toml::Value::String(base)) => {
+                Self::named(base).ok_or_else(|| ThemeError::UnknownTheme(base.clone()))?
+            }
+            Some(other) => return Err(ThemeError::InvalidStyle("base".into(), other.to_string())),
+            None => Self::default_theme(),
+        };
+        for (role, value) in table.iter().filter(|(role, _)| *role != "base") {
+            let toml::Value::String(spec) = value else {
+                return Err(ThemeError::InvalidStyle(role.clone(), value.to_string()));
+            };
+            let style = theme
+                .style_mut(role)
+                .ok_or_else(|| ThemeError::UnknownRole(role.clone()))?;
+            *style = spec
+                .parse()
+                .map_err(|e| ThemeError::InvalidStyle(role.clone(), e))?;
+        }
+        Ok(theme)
+    }
+
+    /// loads overrides from a TOML file, see `from_toml_str`
+    #[cfg(feature = "toml")]
+    pub fn from_file(path: &std::path::Path) -> Result<Self, ThemeError> {
+        Self::from_toml_str(&std::fs::read_to_string(path)?)
+    }
+}
+
+/// Errors while loading a theme
+#[derive(Debug)]
+pub enum ThemeError {
+    Io(std::io::Error),
+    Toml(String),
+    /// `base` names no built-in theme
+    UnknownTheme(String),
+    /// the key names no style of the theme
+    UnknownRole(String),
+    /// role and the reason why its style cannot be parsed
+    InvalidStyle(String, String),
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThemeError::Io(e) => write!(f, "I/O error: {}", e),
+            ThemeError::Toml(e) => write!(f, "TOML error: {}", e),
+            ThemeError::UnknownTheme(name) => write!(f, "unknown theme: {}", name),
+            ThemeError::UnknownRole(role) => write!(f, "unknown style: {}", role),
+            ThemeError::InvalidStyle(role, e) => write!(f, "invalid style for {}: {}", role, e),
+        }
+    }
+}
+
+impl std::error::Error for ThemeError {}
+
+impl From<std::io::Error> for ThemeError {
+    fn from(e: std::io::Error) -> Self {
+        ThemeError::Io(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn styles_parse_colors_and_attributes() {
+        assert_eq!(
+            "#ff8800 bold".parse(),
+            Ok(Style::fg(Color::TrueColor {
+                r: 255,
+                g: 136,
+                b: 0
+            })
+            .bold())
+        );
+        let bright = Style::fg(Color::BrightMagenta).italic();
+        assert_eq!("bright_magenta italic".parse(), Ok(bright));
+        assert_eq!("Italic bright magenta".parse(), Ok(bright));
+        assert_eq!("none".parse(), Ok(Style::plain()));
+        assert_eq!(
+            "dim underline".parse(),
+            Ok(Style::plain().dimmed().underline())
+        );
+    }
+
+    #[test]
+    fn invalid_colors_are_rejected() {
+        assert_eq!(
+            "#ff88 bold".parse::<Style>(),
+            Err("unknown color '#ff88'".to_string())
+        );
+        assert!("#gg0000".parse::<Style>().is_err());
+        assert!("purple-ish".parse::<Style>().is_err());
+    }
+
+    #[test]
+    fn paint_writes_codes_only_for_styled_text() {
+        let style = Style::fg(Color::Red).bold();
+        assert_eq!(style.paint("Error"), "\x1b[1;31mError\x1b[0m");
+        assert_eq!(style.paint(""), "");
+        assert_eq!(Style::plain().paint("Error"), "Error");
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_overrides_apply_to_the_base_theme() {
+        let theme = Theme::from_toml_str(
+            "base = \"high_contrast\"\nwarning = \"#88aaff italic\"\nhint = \"none\"\n",
+        )
+        .unwrap();
+        let expected = Theme {
+            warning: "#88aaff italic".parse().unwrap(),
+            hint: Style::plain(),
+            ..Theme::high_contrast()
+        };
+        assert_eq!(theme, expected);
+        assert_eq!(Theme::from_toml_str("").unwrap(), Theme::default_theme());
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_errors_name_the_problem() {
+        let error = |s: &str| Theme::from_toml_str(s).unwrap_err();
+        assert!(matches!(error("colour = \"red\""), ThemeError::UnknownRole(r) if r == "colour"));
+        assert!(matches!(error("base = \"neon\""), ThemeError::UnknownTheme(b) if b == "neon"));
+        assert!(matches!(error("base = 3"), ThemeError::InvalidStyle(r, _) if r == "base"));
+        assert!(matches!(
+            error("error = \"blinking red\""),
+            ThemeError::InvalidStyle(r, _) if r == "error"
+        ));
+        assert!(matches!(error("error = "), ThemeError::Toml(_)));
+    }
+}
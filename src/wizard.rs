@@ -0,0 +1,405 @@
+use crate::{AdvInput, BACK_INPUT, Navigation, PromptableEnum, SUMMARY_PROMPT_ID};
+use colored::ColoredString;
+use std::str::FromStr;
+
+/* a step asks for its answer with the (progress-prefixed) prompt and the earlier answer */
+type AskFn<'a> = Box<dyn FnMut(&mut AdvInput, ColoredString, Option<&str>) -> Option<String> + 'a>;
+/* decides from the earlier answers whether a step is asked at all */
+type ConditionFn<'a> = Box<dyn Fn(&WizardAnswers) -> bool + 'a>;
+/* checks the answers across steps */
+type RuleFn<'a> = Box<dyn Fn(&WizardAnswers) -> Result<(), Violation> + 'a>;
+
+struct WizardStep<'a> {
+    name: String,
+    prompt: ColoredString,
+    ask: AskFn<'a>,
+    condition: Option<ConditionFn<'a>>,
+}
+
+enum StepOutcome {
+    Answer(String),
+    Invalid,
+    Back,
+    Cancel,
+}
+
+enum SummaryChoice {
+    Confirm,
+    Change(usize),
+    Back,
+    Cancel,
+}
+
+/// A multi-step input flow on top of `AdvInput`.
+/// Every prompt is prefixed with the progress (`Step 3/7`), `:back` or Ctrl-B returns to the
+/// previous step with its earlier answer pre-filled, and a final summary allows jumping to any
+/// step to change it before the answers are returned.
+/// Steps can be made conditional with `when()`, and rules spanning several steps are added with
+/// `validate()`.
+pub struct Wizard<'a> {
+    steps: Vec<WizardStep<'a>>,
+    rules: Vec<RuleFn<'a>>,
+}
+
+impl Default for Wizard<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Wizard<'a> {
+    /// creates an empty wizard; add steps with `step()` or one of the typed `*_step()` functions
+    pub fn new() -> Self {
+        Wizard {
+            steps: Vec::new(),
+            rules: Vec::new(),
+        }
+    }
+
+    /// adds a step that is answered by the given closure. The closure gets the prompt (already
+    /// prefixed with the progress) and the earlier answer of this step, if there is one.
+    /// It returns the answer as text, or `None` if the input was not valid
+    pub fn step<F>(mut self, name: &str, prompt: impl Into<ColoredString>, ask: F) -> Self
+    where
+        F: FnMut(&mut AdvInput, ColoredString, Option<&str>) -> Option<String> + 'a,
+    {
+        self.steps.push(WizardStep {
+            name: name.to_string(),
+            prompt: prompt.into(),
+            ask: Box::new(ask),
+            condition: None,
+        });
+        self
+    }
+
+    /// makes the step added last conditional: it is only asked (and part of the answers) if
+    /// `condition` holds for the answers of the steps before it
+    pub fn when<C>(mut self, condition: C) -> Self
+    where
+        C: Fn(&WizardAnswers) -> bool + 'a,
+    {
+        if let Some(step) = self.steps.last_mut() {
+            step.condition = Some(Box::new(condition));
+        }
+        self
+    }
+
+    /// adds a rule spanning several steps, e.g. "end date after start date". Rules are checked
+    /// once all steps are answered; a `Violation` shows its message and re-prompts its step
+    pub fn validate<R>(mut self, rule: R) -> Self
+    where
+        R: Fn(&WizardAnswers) -> Result<(), Violation> + 'a,
+    {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    /// adds a step asking for a `String`. Like all answers, it is recorded in the history under
+    /// the step name and suggested later; use `PromptHistory::ignore` for secrets
+    pub fn string_step(self, name: &str, prompt: impl Into<ColoredString>) -> Self {
+        self.step(name, prompt, |adv, p, prev| {
+            adv.get_string_initial(p, prev.unwrap_or(""))
+        })
+    }
+
+    /// adds a step asking for a `usize`
+    pub fn index_step(self, name: &str, prompt: impl Into<ColoredString>) -> Self {
+        self.step(name, prompt, |adv, p, prev| {
+            let initial = prev.and_then(|v| v.parse().ok()).unwrap_or(0);
+            adv.get_index_initial(p, initial).map(|v| v.to_string())
+        })
+    }
+
+    /// adds a step asking for a `usize` in the given range (inclusive)
+    pub fn index_range_step(
+        self,
+        name: &str,
+        prompt: impl Into<ColoredString>,
+        low: usize,
+        high: usize,
+    ) -> Self {
+        self.step(name, prompt, move |adv, p, prev| {
+            let initial = prev.and_then(|v| v.parse().ok()).unwrap_or(low);
+            adv.get_index_initial(p, initial)
+                .filter(|v| *v >= low && *v <= high)
+                .map(|v| v.to_string())
+        })
+    }
+
+    /// adds a step asking for a float
+    pub fn f64_step(self, name: &str, prompt: impl Into<ColoredString>) -> Self {
+        self.step(name, prompt, |adv, p, prev| {
+            let initial = prev.and_then(|v| v.parse().ok()).unwrap_or(0.0);
+            adv.get_f64_initial(p, initial).map(|v| v.to_string())
+        })
+    }
+
+    /// adds a step asking for a float in the given range (inclusive)
+    pub fn f64_range_step(
+        self,
+        name: &str,
+        prompt: impl Into<ColoredString>,
+        low: f64,
+        high: f64,
+    ) -> Self {
+        self.step(name, prompt, move |adv, p, prev| {
+            let initial = prev.and_then(|v| v.parse().ok()).unwrap_or(low);
+            adv.get_f64_initial(p, initial)
+                .filter(|v| *v >= low && *v <= high)
+                .map(|v| v.to_string())
+        })
+    }
+
+    /// adds a step asking for a variant of the `PromptableEnum` `E`, with tab completion
+    pub fn enum_step<E>(self, name: &str, prompt: impl Into<ColoredString>) -> Self
+    where
+        E: PromptableEnum,
+    {
+        self.step(name, prompt, |adv, p, prev| {
+            let initial = prev.and_then(E::from_input_str);
+            adv.get_enum_input_initial::<E>(p, initial, true)
+                .map(|v| v.display_name())
+        })
+    }
+
+    /// runs all steps and the final summary.
+    /// Returns the answers once the summary is confirmed, or `None` if the user cancelled
+    /// (Ctrl-C / Ctrl-D)
+    pub fn run(&mut self, adv: &mut AdvInput) -> Option<WizardAnswers> {
+        adv.set_navigation(true);
+        println!(
+            "{}",
+            adv.out_theme().hint.paint(&format!(
+                "(enter {} or press Ctrl-B to return to the previous step)",
+                BACK_INPUT
+            ))
+        );
+        let result = self.run_steps(adv);
+        adv.set_navigation(false);
+        result
+    }
+
+    fn run_steps(&mut self, adv: &mut AdvInput) -> Option<WizardAnswers> {
+        let total = self.steps.len();
+        let mut answers: Vec<Option<String>> = vec![None; total];
+        let mut idx = 0;
+        // true while a single step is changed from the summary
+        let mut from_summary = false;
+        loop {
+            let active = self.active_steps(&answers);
+            // skip steps whose condition does not hold
+            while idx < total && !active[idx] {
+                idx += 1;
+            }
+            if idx >= total {
+                if let Some(violation) = self.first_violation(&self.collect(&answers)) {
+                    eprintln!("{}", adv.err_theme().error.paint(&violation.message));
+                    idx = self
+                        .steps
+                        .iter()
+                        .position(|s| s.name == violation.step)
+                        .filter(|i| active[*i])
+                        .or_else(|| active.iter().rposition(|a| *a))
+                        .unwrap_or(0);
+                    from_summary = true;
+                    continue;
+                }
+                match self.summary(adv, &answers, &active) {
+                    SummaryChoice::Confirm => return Some(self.collect(&answers)),
+                    SummaryChoice::Change(i) => {
+                        idx = i;
+                        from_summary = true;
+                    }
+                    SummaryChoice::Back => {
+                        idx = active.iter().rposition(|a| *a).unwrap_or(total);
+                    }
+                    SummaryChoice::Cancel => return None,
+                }
+                continue;
+            }
+            match self.ask_step(adv, idx, &active, answers[idx].as_deref()) {
+                StepOutcome::Answer(answer) => {
+                    answers[idx] = Some(answer);
+                    idx = if from_summary {
+                        // the change may have enabled steps that still need an answer
+                        let active = self.active_steps(&answers);
+                        (0..total)
+                            .find(|i| active[*i] && answers[*i].is_none())
+                            .unwrap_or(total)
+                    } else {
+                        idx + 1
+                    };
+                }
+                StepOutcome::Invalid => {
+                    eprintln!(
+                        "{}",
+                        adv.err_theme()
+                            .warning
+                            .paint("Invalid input, please try again.")
+                    );
+                }
+                StepOutcome::Back => {
+                    from_summary = false;
+                    if let Some(prev) = active[..idx].iter().rposition(|a| *a) {
+                        idx = prev;
+                    }
+                }
+                StepOutcome::Cancel => return None,
+            }
+        }
+    }
+
+    fn ask_step(
+        &mut self,
+        adv: &mut AdvInput,
+        idx: usize,
+        active: &[bool],
+        previous: Option<&str>,
+    ) -> StepOutcome {
+        let number = active[..=idx].iter().filter(|a| **a).count();
+        let total = active.iter().filter(|a| **a).count();
+        let step = &mut self.steps[idx];
+        let prompt = ColoredString::from(format!(
+            "{} {}",
+            adv.out_theme()
+                .hint
+                .paint(&format!("Step {}/{}", number, total)),
+            step.prompt
+        ));
+        adv.prompt_id(&step.name);
+        match (step.ask)(adv, prompt, previous) {
+            Some(answer) => StepOutcome::Answer(answer),
+            None => match adv.take_navigation() {
+                Some(Navigation::Back) => StepOutcome::Back,
+                Some(Navigation::Cancel) => StepOutcome::Cancel,
+                None => StepOutcome::Invalid,
+            },
+        }
+    }
+
+    fn summary(
+        &self,
+        adv: &mut AdvInput,
+        answers: &[Option<String>],
+        active: &[bool],
+    ) -> SummaryChoice {
+        let shown: Vec<usize> = (0..self.steps.len()).filter(|i| active[*i]).collect();
+        println!("{}", adv.out_theme().heading.paint("Summary:"));
+        for (n, i) in shown.iter().enumerate() {
+            println!(
+                "  {}) {}: {}",
+                n + 1,
+                self.steps[*i].name,
+                adv.out_theme()
+                    .highlight
+                    .paint(answers[*i].as_deref().unwrap_or("-"))
+            );
+        }
+        loop {
+            match adv
+                .prompt_id(SUMMARY_PROMPT_ID)
+                .get_string("Step number to change, Enter to confirm: ")
+            {
+                Some(line) if line.is_empty() => return SummaryChoice::Confirm,
+                Some(line) => match line.parse::<usize>() {
+                    Ok(n) if n >= 1 && n <= shown.len() => {
+                        return SummaryChoice::Change(shown[n - 1]);
+                    }
+                    _ => eprintln!(
+                        "{}",
+                        adv.err_theme()
+                            .warning
+                            .paint(&format!("Please enter a number from 1 to {}.", shown.len()))
+                    ),
+                },
+                None => match adv.take_navigation() {
+                    Some(Navigation::Back) => return SummaryChoice::Back,
+                    _ => return SummaryChoice::Cancel,
+                },
+            }
+        }
+    }
+
+    /* a step is active if its condition holds for the answers of the active steps before it */
+    fn active_steps(&self, answers: &[Option<String>]) -> Vec<bool> {
+        let mut known = WizardAnswers::default();
+        let mut active = Vec::with_capacity(self.steps.len());
+        for (step, answer) in self.steps.iter().zip(answers) {
+            let is_active = step.condition.as_ref().is_none_or(|cond| cond(&known));
+            if is_active && let Some(a) = answer {
+                known.entries.push((step.name.clone(), a.clone()));
+            }
+            active.push(is_active);
+        }
+        active
+    }
+
+    fn first_violation(&self, answers: &WizardAnswers) -> Option<Violation> {
+        self.rules.iter().find_map(|rule| rule(answers).err())
+    }
+
+    /* only answers of active steps are part of the result */
+    fn collect(&self, answers: &[Option<String>]) -> WizardAnswers {
+        let active = self.active_steps(answers);
+        WizardAnswers {
+            entries: self
+                .steps
+                .iter()
+                .zip(answers)
+                .zip(active)
+                .filter(|(_, is_active)| *is_active)
+                .filter_map(|((step, answer), _)| {
+                    answer.as_ref().map(|a| (step.name.clone(), a.clone()))
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A rule violation reported by a `Wizard::validate()` rule: the step to re-prompt and the
+/// message shown to the user
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    pub step: String,
+    pub message: String,
+}
+
+impl Violation {
+    pub fn new(step: &str, message: impl Into<String>) -> Self {
+        Violation {
+            step: step.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+/// The answers of a completed `Wizard`, stored as text by step name
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WizardAnswers {
+    entries: Vec<(String, String)>,
+}
+
+impl WizardAnswers {
+    /// returns the answer of the step with the given name
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// parses the answer of the step with the given name, e.g. into a number
+    pub fn parse<T: FromStr>(&self, name: &str) -> Option<T> {
+        self.get(name).and_then(|v| v.parse().ok())
+    }
+
+    /// returns the answer of an `enum_step()` as its variant
+    pub fn get_enum<E: PromptableEnum>(&self, name: &str) -> Option<E> {
+        self.get(name).and_then(E::from_input_str)
+    }
+
+    /// iterates over all `(name, answer)` pairs in step order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries.iter().map(|(n, v)| (n.as_str(), v.as_str()))
+    }
+}